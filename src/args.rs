@@ -1,5 +1,12 @@
+use crate::audius::genre::{Genre, GENRES};
+use crate::audius::trending::TimeRange;
+use crate::date::Date;
+use crate::filter::TrackFilter;
+use crate::playable::Playable;
+use crate::sort::{SortDirection, SortKey, SortOrder};
 use crate::ui::UiVariant;
-use crate::PlayOrder;
+use crate::{GroupMode, PlayOrder, ShuffleWeight};
+use regex::Regex;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::num::ParseIntError;
@@ -40,6 +47,7 @@ impl PicoParsable<PlayOrder> for PlayOrder {
             "asc" => Ok(PlayOrder::Ascending),
             "desc" => Ok(PlayOrder::Descending),
             "rand" => Ok(PlayOrder::Random),
+            "smart" => Ok(PlayOrder::Smart),
             _ => Err(ParseArgError {
                 details: str.to_owned() + " is not a valid order",
             }),
@@ -47,6 +55,52 @@ impl PicoParsable<PlayOrder> for PlayOrder {
     }
 }
 
+impl PicoParsable<ShuffleWeight> for ShuffleWeight {
+    fn pico_parse(str: &str) -> Result<ShuffleWeight, ParseArgError> {
+        match str {
+            "plays" => Ok(ShuffleWeight::Plays),
+            "rank" => Ok(ShuffleWeight::Rank),
+            _ => Err(ParseArgError {
+                details: str.to_owned() + " is not a valid shuffle weight",
+            }),
+        }
+    }
+}
+
+impl PicoParsable<SortOrder> for SortOrder {
+    fn pico_parse(str: &str) -> Result<SortOrder, ParseArgError> {
+        let mut keys = Vec::new();
+        for part in str.split(',') {
+            let (key, direction) = part.split_once(':').unwrap_or((part, "asc"));
+            let key = match key {
+                "artist" => SortKey::Artist,
+                "duration" => SortKey::Duration,
+                "favorites" => SortKey::Favorites,
+                "plays" => SortKey::Plays,
+                "released" => SortKey::Released,
+                "title" => SortKey::Title,
+                _ => {
+                    return Err(ParseArgError {
+                        details: key.to_owned() + " is not a valid sort key",
+                    })
+                }
+            };
+            let direction = match direction {
+                "asc" => SortDirection::Ascending,
+                "desc" => SortDirection::Descending,
+                _ => {
+                    return Err(ParseArgError {
+                        details: direction.to_owned() + " is not a valid sort direction",
+                    })
+                }
+            };
+            keys.push((key, direction));
+        }
+
+        Ok(SortOrder { keys })
+    }
+}
+
 impl PicoParsable<UiVariant> for UiVariant {
     fn pico_parse(str: &str) -> Result<UiVariant, ParseArgError> {
         match str.to_lowercase().as_str() {
@@ -67,7 +121,7 @@ impl PicoParsable<Duration> for Duration {
         let mut cur = String::new();
         let mut parts = Vec::new();
         for char in str.chars() {
-            if char.is_digit(10) {
+            if char.is_ascii_digit() {
                 if !was_digit {
                     parts.push(cur);
                     cur = String::new();
@@ -78,14 +132,14 @@ impl PicoParsable<Duration> for Duration {
             }
             cur.push(char);
         }
-        if !was_digit {
+        if !cur.is_empty() {
             parts.push(cur);
         }
 
         fn split_part(str: &str) -> (&str, &str) {
             // Split into number and unit
-            let desc_index = str.chars().position(|c| !c.is_digit(10)).unwrap();
-            str.split_at(desc_index)
+            let desc_index = str.chars().position(|c| !c.is_ascii_digit());
+            str.split_at(desc_index.unwrap_or(str.len()))
         }
 
         // Calculate duration
@@ -93,6 +147,12 @@ impl PicoParsable<Duration> for Duration {
         for (num, unit) in parts.iter().map(|p| split_part(p)) {
             let num = str::parse::<u64>(num)?;
             match unit {
+                "w" => {
+                    duration += Duration::from_secs(num * 7 * 86400);
+                }
+                "d" => {
+                    duration += Duration::from_secs(num * 86400);
+                }
                 "h" => {
                     duration += Duration::from_secs(num * 3600);
                 }
@@ -102,6 +162,11 @@ impl PicoParsable<Duration> for Duration {
                 "s" => {
                     duration += Duration::from_secs(num);
                 }
+                "" => {
+                    return Err(ParseArgError {
+                        details: num.to_string() + " is missing a unit (w, d, h, m or s)",
+                    });
+                }
                 _ => {
                     return Err(ParseArgError {
                         details: unit.to_owned() + " is not a valid unit",
@@ -114,70 +179,116 @@ impl PicoParsable<Duration> for Duration {
     }
 }
 
-pub struct TrendingPlayable {
-    pub(crate) genre: Option<String>,
-    pub(crate) time: Option<String>,
+impl PicoParsable<Date> for Date {
+    fn pico_parse(str: &str) -> Result<Date, ParseArgError> {
+        Date::parse_iso(str).ok_or_else(|| ParseArgError {
+            details: str.to_owned() + " is not a valid date (expected YYYY-MM-DD)",
+        })
+    }
+}
+
+impl PicoParsable<Genre> for Genre {
+    fn pico_parse(str: &str) -> Result<Genre, ParseArgError> {
+        Genre::parse(str).map_err(|details| ParseArgError { details })
+    }
 }
 
-pub fn is_trending(arg: &str) -> bool {
-    arg.starts_with("trending")
+impl PicoParsable<TimeRange> for TimeRange {
+    fn pico_parse(str: &str) -> Result<TimeRange, ParseArgError> {
+        TimeRange::parse(str).map_err(|details| ParseArgError { details })
+    }
 }
 
-pub fn parse_trending_arg(arg: &str) -> TrendingPlayable {
-    let splitted = arg.split(':').collect::<Vec<&str>>();
-    let genre = if splitted.len() > 1 {
-        if !splitted[1].is_empty() {
-            Some(splitted[1].to_string())
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-    let time = if splitted.len() > 2 {
-        if !splitted[2].is_empty() {
-            Some(splitted[2].to_string())
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-    TrendingPlayable { genre, time }
+impl PicoParsable<Playable> for Playable {
+    fn pico_parse(str: &str) -> Result<Playable, ParseArgError> {
+        Playable::parse(str).map_err(|details| ParseArgError { details })
+    }
 }
 
 // Log is only read when the ncurses feature is turned on
 #[allow(dead_code)]
 pub struct ConsoleArgs {
-    pub(crate) genre: Option<String>,
-    pub(crate) max_length: Option<Duration>,
-    pub(crate) min_length: Option<Duration>,
+    pub(crate) filter: TrackFilter,
+    pub(crate) genre: Option<Genre>,
+    pub(crate) group_mode: GroupMode,
+    pub(crate) keep_duplicates: bool,
+    pub(crate) limit: Option<usize>,
     pub(crate) order: PlayOrder,
-    pub(crate) playables: Vec<String>,
+    pub(crate) playables: Vec<Playable>,
+    pub(crate) resume: bool,
+    pub(crate) seed: Option<u64>,
+    pub(crate) shuffle_weight: Option<ShuffleWeight>,
+    pub(crate) skip_played: Option<Duration>,
+    pub(crate) sort: Option<SortOrder>,
     pub(crate) ui: UiVariant,
-    pub(crate) time: Option<String>,
+    pub(crate) time: Option<TimeRange>,
     pub(crate) volume: f32,
 }
 
 pub fn handle_args() -> Option<ConsoleArgs> {
     let mut args = pico_args::Arguments::from_env();
-    let genre = args
-        .opt_value_from_str(["-g", "--genre"])
-        .expect("parsing genre");
+    let genre = args.opt_value_from_fn(["-g", "--genre"], Genre::pico_parse);
     let help = args.contains(["-h", "--help"]);
-    let max_length: Option<Duration> = args
-        .opt_value_from_fn("--max-length", Duration::pico_parse)
-        .expect("parsing max-length");
-    let min_length: Option<Duration> = args
-        .opt_value_from_fn("--min-length", Duration::pico_parse)
-        .expect("parsing min-length");
+    let interleave = args.contains("--interleave");
+    let keep_duplicates = args.contains("--keep-duplicates");
+    let merge = args.contains("--merge");
+    let limit = args.opt_value_from_str("--limit").expect("parsing limit");
+    let list_genres = args.contains("--list-genres");
+    let filter = TrackFilter {
+        min_length: args
+            .opt_value_from_fn("--min-length", Duration::pico_parse)
+            .expect("parsing min-length"),
+        max_length: args
+            .opt_value_from_fn("--max-length", Duration::pico_parse)
+            .expect("parsing max-length"),
+        include_genres: args
+            .values_from_str("--include-genre")
+            .expect("parsing include-genre"),
+        exclude_genres: args
+            .values_from_str("--exclude-genre")
+            .expect("parsing exclude-genre"),
+        include_moods: args
+            .values_from_str("--include-mood")
+            .expect("parsing include-mood"),
+        exclude_moods: args
+            .values_from_str("--exclude-mood")
+            .expect("parsing exclude-mood"),
+        include_tags: args
+            .values_from_str("--include-tag")
+            .expect("parsing include-tag"),
+        exclude_tags: args
+            .values_from_str("--exclude-tag")
+            .expect("parsing exclude-tag"),
+        min_plays: args
+            .opt_value_from_str("--min-plays")
+            .expect("parsing min-plays"),
+        min_favorites: args
+            .opt_value_from_str("--min-favorites")
+            .expect("parsing min-favorites"),
+        released_after: args
+            .opt_value_from_fn("--released-after", Date::pico_parse)
+            .expect("parsing released-after"),
+        released_before: args
+            .opt_value_from_fn("--released-before", Date::pico_parse)
+            .expect("parsing released-before"),
+        title_regex: args
+            .opt_value_from_fn("--title-regex", Regex::new)
+            .expect("parsing title-regex"),
+    };
     let order: PlayOrder = args
         .opt_value_from_fn(["-o", "--order"], PlayOrder::pico_parse)
         .expect("parsing order")
         .unwrap_or(PlayOrder::Ascending);
-    let time = args
-        .opt_value_from_str(["-t", "--time"])
-        .expect("parsing time");
+    let resume = args.contains("--resume");
+    let seed = args.opt_value_from_str("--seed").expect("parsing seed");
+    let shuffle_weight = args
+        .opt_value_from_fn("--shuffle-weight", ShuffleWeight::pico_parse)
+        .expect("parsing shuffle weight");
+    let skip_played = args.opt_value_from_fn("--skip-played", Duration::pico_parse);
+    let sort = args
+        .opt_value_from_fn("--sort", SortOrder::pico_parse)
+        .expect("parsing sort order");
+    let time = args.opt_value_from_fn(["-t", "--time"], TimeRange::pico_parse);
     let ui = args
         .opt_value_from_fn("--ui", UiVariant::pico_parse)
         .expect("parsing ui variant")
@@ -189,11 +300,12 @@ pub fn handle_args() -> Option<ConsoleArgs> {
         .unwrap_or_default()
         .min(0.0);
 
-    let playables = args
+    let playables: Result<Vec<Playable>, ParseArgError> = args
         .finish()
         .into_iter()
         .map(|s| s.to_str().unwrap_or_default().to_string())
         .filter(|arg| !arg.starts_with('-'))
+        .map(|arg| Playable::pico_parse(&arg))
         .collect();
 
     if help {
@@ -201,39 +313,127 @@ pub fn handle_args() -> Option<ConsoleArgs> {
         return None;
     }
 
+    let group_mode = match (merge, interleave) {
+        (false, false) => GroupMode::Separate,
+        (true, false) => GroupMode::Merge,
+        (false, true) => GroupMode::Interleave,
+        (true, true) => {
+            eprintln!("Error: --merge and --interleave can't be used together");
+            return None;
+        }
+    };
+
     if version {
         println!("halkara {}", env!("CARGO_PKG_VERSION"));
         return None;
     }
 
+    if list_genres {
+        for genre in GENRES {
+            println!("{}", genre);
+        }
+        return None;
+    }
+
+    let (genre, time, skip_played) = match (genre, time, skip_played) {
+        (Ok(genre), Ok(time), Ok(skip_played)) => (genre, time, skip_played),
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+            eprintln!("Error: {}", option_error(err));
+            return None;
+        }
+    };
+
+    // Reject malformed playables before anything is fetched
+    let playables = match playables {
+        Ok(playables) => playables,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return None;
+        }
+    };
+
     Some(ConsoleArgs {
+        filter,
         genre,
-        min_length,
-        max_length,
+        group_mode,
+        keep_duplicates,
+        limit,
         order,
         playables,
+        resume,
+        seed,
+        shuffle_weight,
+        skip_played,
+        sort,
         time,
         ui,
         volume,
     })
 }
 
+/// Extracts the message of an invalid option value
+fn option_error(err: pico_args::Error) -> String {
+    match err {
+        pico_args::Error::Utf8ArgumentParsingFailed { cause, .. } => cause,
+        err => err.to_string(),
+    }
+}
+
 fn print_help() {
     println!(
         "USAGE:
     halkara [OPTIONS] [URLS]
+    halkara ctl <COMMAND>
 
 OPTIONS:
-    -g, --genre <GENRE>      Selects the trending tracks for a specified genre
-    -h, --help               Print help information
-        --max-length         The maximum length for a track (longer tracks won't be played)
-        --min-length         The minimum length for a track (shorter tracks won't be played)
-    -o, --order <ORDER>      The order in which to play the trending tracks [possible values: asc,
-                             desc, rand]
-    -t, --time <TIME>        Selects the trending tracks over a specified time range
-        --ui <UI>            The user interface variant to use [possible values: compact, log,
-                             ncurses]
-    -V, --version            Print version information
-        --volume <VOLUME>    The volume in dBFS"
+        --exclude-genre <GENRE>     Skips tracks of the specified genre (can be used multiple times)
+        --exclude-mood <MOOD>       Skips tracks with the specified mood (can be used multiple
+                                    times)
+        --exclude-tag <TAG>         Skips tracks with the specified tag (can be used multiple times)
+    -g, --genre <GENRE>             Selects the trending tracks for a specified genre (see
+                                    --list-genres)
+    -h, --help                      Print help information
+        --include-genre <GENRE>     Only plays tracks of the specified genre (can be used multiple
+                                    times)
+        --include-mood <MOOD>       Only plays tracks with the specified mood (can be used multiple
+                                    times)
+        --include-tag <TAG>         Only plays tracks with the specified tag (can be used multiple
+                                    times)
+        --interleave                Plays the tracks of all playables alternately (one track of each
+                                    playable in turn)
+        --keep-duplicates           Plays tracks appearing in multiple playables more than once
+        --limit <COUNT>             The maximum number of tracks of each playable (for trending
+                                    playlists: the maximum number of playlists)
+        --list-genres               Prints the genres known to Audius
+        --max-length                The maximum length for a track (longer tracks won't be played)
+        --merge                     Merges the tracks of all playables into a single queue before
+                                    ordering them
+        --min-favorites <COUNT>     The minimum number of favorites for a track
+        --min-length                The minimum length for a track (shorter tracks won't be played)
+        --min-plays <COUNT>         The minimum play count for a track
+    -o, --order <ORDER>             The order in which to play the trending tracks [possible values:
+                                    asc, desc, rand, smart]
+        --released-after <DATE>     Only plays tracks released on or after the specified date
+                                    (YYYY-MM-DD)
+        --released-before <DATE>    Only plays tracks released on or before the specified date
+                                    (YYYY-MM-DD)
+        --resume                    Resumes the session saved when quitting the last time
+        --seed <SEED>               The seed for shuffling (the seed of a shuffled session is
+                                    printed at startup)
+        --shuffle-weight <WEIGHT>   Prefers popular tracks of an artist when using the smart order
+                                    [possible values: plays, rank]
+        --skip-played <DURATION>    Skips tracks that have been played within the specified duration
+                                    (e.g. 1w or 2d12h)
+        --sort <KEYS>               Sorts the tracks by the specified keys, each optionally followed
+                                    by a direction (e.g. plays:desc,title) [possible keys: artist,
+                                    duration, favorites, plays, released, title] [possible
+                                    directions: asc, desc]
+    -t, --time <TIME>               Selects the trending tracks over a specified time range
+                                    [possible values: week, month, year, allTime]
+        --title-regex <REGEX>       Only plays tracks with a title matching the regular expression
+        --ui <UI>                   The user interface variant to use [possible values: compact,
+                                    log, ncurses]
+    -V, --version                   Print version information
+        --volume <VOLUME>           The volume in dBFS"
     );
 }
@@ -1,17 +1,23 @@
-use std::sync::RwLock;
+use std::collections::HashSet;
+use std::sync::{PoisonError, RwLock};
 use std::time::Instant;
 
+use crate::playable::UrlKind;
 use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
 use playlist::PlaylistResponse;
 use track::{Track, TrackResponse, TracksResponse};
 use user::UserResponse;
 
-mod playlist;
+pub mod feed;
+pub mod genre;
+pub mod playlist;
+pub mod radio;
 pub mod track;
 pub mod trending;
-mod user;
+pub mod user;
 
 #[derive(Deserialize)]
 struct ApiResponse {
@@ -21,6 +27,8 @@ struct ApiResponse {
 pub struct OrderedTrack {
     pub track: Track,
     pub index: usize,
+    /// Name of the group the track originally belonged to (if it has been moved to another group)
+    pub origin: Option<String>,
 }
 
 pub struct TrackGroup {
@@ -28,11 +36,95 @@ pub struct TrackGroup {
     pub name: String,
 }
 
+impl TrackGroup {
+    /// Flattens all groups into a single group (one after another)
+    pub fn merge(track_groups: Vec<TrackGroup>) -> TrackGroup {
+        TrackGroup {
+            tracks: track_groups
+                .into_iter()
+                .flat_map(TrackGroup::into_tracks_with_origin)
+                .collect(),
+            name: String::from("Queue"),
+        }
+    }
+
+    /// Flattens all groups into a single group by taking one track of each group in turn
+    pub fn interleave(track_groups: Vec<TrackGroup>) -> TrackGroup {
+        let mut iters: Vec<_> = track_groups
+            .into_iter()
+            .map(|group| group.into_tracks_with_origin().into_iter())
+            .collect();
+        let mut tracks = Vec::new();
+        loop {
+            let len = tracks.len();
+            tracks.extend(iters.iter_mut().filter_map(|iter| iter.next()));
+            if tracks.len() == len {
+                break;
+            }
+        }
+
+        TrackGroup {
+            tracks,
+            name: String::from("Queue"),
+        }
+    }
+
+    /// Removes tracks that are in `seen` already (the remaining tracks are added to `seen`)
+    ///
+    /// Returns the number of removed tracks.
+    pub fn remove_duplicates(track_groups: &mut [TrackGroup], seen: &mut HashSet<String>) -> usize {
+        let mut removed = 0;
+        for group in track_groups.iter_mut() {
+            let len = group.tracks.len();
+            group.tracks.retain(|t| seen.insert(t.track.id.clone()));
+            removed += len - group.tracks.len();
+        }
+
+        removed
+    }
+
+    pub fn into_tracks_with_origin(self) -> Vec<OrderedTrack> {
+        let name = self.name;
+        self.tracks
+            .into_iter()
+            .map(|mut track| {
+                track.origin.get_or_insert_with(|| name.clone());
+                track
+            })
+            .collect()
+    }
+}
+
+/// Page of a list endpoint
+#[derive(Deserialize)]
+struct Page<T> {
+    data: Vec<T>,
+}
+
+/// Item of a list endpoint with an id to recognize pages that have been returned before
+trait ListItem {
+    fn id(&self) -> &str;
+}
+
+impl ListItem for Track {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
 struct ApiCache {
     url: String,
     timestamp: Option<Instant>,
 }
 
+impl ApiCache {
+    /// The cached endpoint unless it's outdated
+    fn get(&self) -> Option<String> {
+        let timestamp = self.timestamp?;
+        (Instant::now().duration_since(timestamp).as_secs() < 3600).then(|| self.url.clone())
+    }
+}
+
 lazy_static! {
     static ref API_CACHE: RwLock<ApiCache> = RwLock::new(ApiCache {
         url: String::new(),
@@ -42,70 +134,155 @@ lazy_static! {
 
 const APP_NAME: &str = "Halkara";
 
-fn get_api() -> String {
-    let mut update_cache = true;
-    let mut url = String::new();
-    if let Ok(cache) = API_CACHE.read() {
-        if let Some(timestamp) = cache.timestamp {
-            if Instant::now().duration_since(timestamp).as_secs() < 3600 {
-                update_cache = false;
-                url = cache.url.clone();
+/// Number of items per request for endpoints supporting pagination
+const PAGE_SIZE: usize = 100;
+
+/// Maximum number of requests sent at the same time
+pub const WORKERS: usize = 4;
+
+fn get_api() -> Result<String, String> {
+    if let Some(url) = API_CACHE.read().ok().and_then(|cache| cache.get()) {
+        return Ok(url);
+    }
+
+    // Only one thread looks for an endpoint while the others wait for the result
+    let mut cache = API_CACHE.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(url) = cache.get() {
+        return Ok(url);
+    }
+    let url = find_api()?;
+
+    // Cache for next call
+    cache.url = url.clone();
+    cache.timestamp = Some(Instant::now());
+    Ok(url)
+}
+
+/// Selects the first working endpoint from the list of API endpoints
+fn find_api() -> Result<String, String> {
+    let api_res: ApiResponse = ureq::get("https://api.audius.co")
+        .call()
+        .map_err(|_| "Unable to execute GET request for the list of API endpoints".to_string())?
+        .into_json()
+        .map_err(|_| "Unable to deserialize the list of API endpoints".to_string())?;
+    let mut endpoints = api_res.data.into_iter();
+
+    loop {
+        // Select endpoint
+        let url = endpoints
+            .next()
+            .ok_or("Unable to find a working API endpoint")?
+            + "/v1/";
+
+        // Test endpoint
+        let resp_result = ureq::get(format!("{}tracks/QxamW", url).as_str())
+            .query("app_name", APP_NAME)
+            .call();
+        if let Ok(resp) = resp_result {
+            let track: Result<TrackResponse, _> = resp.into_json();
+            if track.is_ok() {
+                return Ok(url);
             }
         }
     }
+}
 
-    if update_cache {
-        let api_res: ApiResponse = ureq::get("https://api.audius.co")
+/// Fetches the tracks of a list endpoint page by page (up to the limit)
+fn get_tracks_paginated(
+    url: &str,
+    query: &[(&str, &str)],
+    limit: Option<usize>,
+    description: &str,
+) -> Result<Vec<Track>, String> {
+    get_paginated(url, query, limit, description, Some)
+}
+
+/// Fetches the items of a list endpoint page by page until the limit of items is reached
+///
+/// Items that are skipped by `into_item` don't count towards the limit.
+fn get_paginated<T, R, F>(
+    url: &str,
+    query: &[(&str, &str)],
+    limit: Option<usize>,
+    description: &str,
+    into_item: F,
+) -> Result<Vec<R>, String>
+where
+    T: DeserializeOwned,
+    R: ListItem,
+    F: Fn(T) -> Option<R>,
+{
+    let mut items: Vec<R> = Vec::new();
+    let mut offset = 0;
+    let mut last_first_id = None;
+    loop {
+        let page_size = limit.map_or(PAGE_SIZE, |limit| {
+            PAGE_SIZE.min(limit.saturating_sub(items.len()))
+        });
+        if page_size == 0 {
+            break;
+        }
+
+        let mut request = ureq::get(url)
+            .query("app_name", APP_NAME)
+            .query("offset", &offset.to_string())
+            .query("limit", &page_size.to_string());
+        for (param, value) in query {
+            request = request.query(param, value);
+        }
+        let page: Page<T> = request
             .call()
-            .unwrap()
+            .map_err(|_| format!("Unable to execute GET request for {}", description))?
             .into_json()
-            .unwrap();
-        let mut endpoints = api_res.data.into_iter();
-
-        let mut works = false;
-        while !works {
-            // Select endpoint
-            url = endpoints.next().expect("Selecting API endpoint") + "/v1/";
-
-            // Test endpoint
-            let resp_result = ureq::get(format!("{}tracks/QxamW", url).as_str())
-                .query("app_name", APP_NAME)
-                .call();
-            if let Ok(resp) = resp_result {
-                let track: Result<TrackResponse, _> = resp.into_json();
-                if track.is_ok() {
-                    works = true;
-                }
-            }
-        }
+            .map_err(|_| format!("Unable to deserialize {}", description))?;
+
+        let len = page.data.len();
+        let page_items: Vec<R> = page.data.into_iter().filter_map(&into_item).collect();
 
-        if let Ok(mut cache) = API_CACHE.write() {
-            // Cache for next call
-            cache.url = url.clone();
-            cache.timestamp = Some(Instant::now());
+        // Stop if the endpoint doesn't support pagination and returns the same items again
+        let first_id = page_items.first().map(|item| item.id().to_string());
+        if len == 0 || (first_id.is_some() && first_id == last_first_id) {
+            break;
+        }
+        offset += len;
+        items.extend(page_items);
+        if len < page_size {
+            break;
         }
+        last_first_id = first_id;
     }
 
-    url
+    if let Some(limit) = limit {
+        items.truncate(limit);
+    }
+    Ok(items)
 }
 
-pub fn resolve(url: &str) -> Result<Vec<TrackGroup>, String> {
-    let api = get_api();
+/// Resolves an Audius URL into the track groups of the track, playlist or user behind it
+pub fn resolve(url: &str, kind: &UrlKind, limit: Option<usize>) -> Result<Vec<TrackGroup>, String> {
+    let api = get_api()?;
     let resp = ureq::get(format!("{}resolve", api).as_str())
         .query("app_name", APP_NAME)
         .query("url", url)
         .call()
-        .unwrap_or_else(|_| panic!("Unable to execute GET request for {}", url))
+        .map_err(|_| format!("Unable to execute GET request for {}", url))?
         .into_string()
-        .expect("Extracting string from resolve response");
-
-    if let Ok(playlist_response) = ureq::serde_json::from_str::<PlaylistResponse>(&resp) {
-        Ok(playlist_response.track_groups())
-    } else if let Ok(tracks_response) = ureq::serde_json::from_str::<TracksResponse>(&resp) {
-        Ok(vec![tracks_response.track_group()])
-    } else if let Ok(user_response) = ureq::serde_json::from_str::<UserResponse>(&resp) {
-        Ok(vec![user_response.track_group()])
-    } else {
-        Err(format!("Unable to resolve {}", url))
+        .map_err(|_| format!("Unable to read the response for {}", url))?;
+
+    let error = |_| format!("Unable to resolve {}", url);
+    match kind {
+        UrlKind::Playlist => ureq::serde_json::from_str::<PlaylistResponse>(&resp)
+            .map_err(error)?
+            .track_groups(limit),
+        UrlKind::Track => {
+            let track_response: TrackResponse = ureq::serde_json::from_str(&resp).map_err(error)?;
+            let tracks_response = TracksResponse {
+                data: vec![track_response.data],
+            };
+            Ok(vec![tracks_response.track_group()])
+        }
+        UrlKind::User => Ok(vec![ureq::serde_json::from_str::<UserResponse>(&resp)
+            .map_err(error)?
+            .track_group(limit)?]),
     }
 }
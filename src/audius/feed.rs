@@ -0,0 +1,50 @@
+use super::track::TracksResponse;
+use super::{get_api, TrackGroup, APP_NAME};
+
+/// Curated track lists of Audius
+pub enum Feed {
+    BestNewReleases,
+    FeelingLucky,
+    Remixables,
+    Underground,
+}
+
+impl Feed {
+    fn endpoint(&self) -> &str {
+        match self {
+            Feed::BestNewReleases => "full/tracks/best_new_releases",
+            Feed::FeelingLucky => "full/tracks/feeling_lucky",
+            Feed::Remixables => "full/tracks/remixables",
+            Feed::Underground => "tracks/trending/underground",
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Feed::BestNewReleases => "Best new releases",
+            Feed::FeelingLucky => "Feeling lucky",
+            Feed::Remixables => "Remixables",
+            Feed::Underground => "Underground trending tracks",
+        }
+    }
+}
+
+pub fn get_feed(feed: &Feed) -> Result<TrackGroup, String> {
+    let api = get_api()?;
+
+    let feed_url = format!("{}{}", api, feed.endpoint());
+    let mut request = ureq::get(&feed_url).query("app_name", APP_NAME);
+    if let Feed::BestNewReleases = feed {
+        request = request.query("window", "week");
+    }
+    let feed_res: TracksResponse = request
+        .call()
+        .map_err(|_| format!("Unable to execute GET request for {}", feed.name()))?
+        .into_json()
+        .map_err(|_| format!("Unable to deserialize {}", feed.name()))?;
+
+    // The index is the track's rank within the feed
+    let mut track_group = feed_res.track_group();
+    track_group.name = feed.name().to_string();
+    Ok(track_group)
+}
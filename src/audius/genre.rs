@@ -0,0 +1,87 @@
+use crate::utils::edit_distance;
+
+/// Genres known to Audius
+pub const GENRES: &[&str] = &[
+    "Acoustic",
+    "Alternative",
+    "Ambient",
+    "Audiobooks",
+    "Blues",
+    "Classical",
+    "Comedy",
+    "Country",
+    "Dancehall",
+    "Deep House",
+    "Devotional",
+    "Disco",
+    "Downtempo",
+    "Drum & Bass",
+    "Dubstep",
+    "Electro",
+    "Electronic",
+    "Experimental",
+    "Folk",
+    "Funk",
+    "Future Bass",
+    "Future House",
+    "Glitch Hop",
+    "Hardstyle",
+    "Hip-Hop/Rap",
+    "House",
+    "Hyperpop",
+    "Jazz",
+    "Jersey Club",
+    "Jungle",
+    "Kids",
+    "Latin",
+    "Lo-Fi",
+    "Metal",
+    "Moombahton",
+    "Podcasts",
+    "Pop",
+    "Progressive House",
+    "Punk",
+    "R&B/Soul",
+    "Reggae",
+    "Rock",
+    "Soundtrack",
+    "Spoken Word",
+    "Tech House",
+    "Techno",
+    "Trance",
+    "Trap",
+    "Tropical House",
+    "Vaporwave",
+    "World",
+];
+
+/// One of the genres known to Audius
+#[derive(Clone, Copy)]
+pub struct Genre(&'static str);
+
+impl Genre {
+    /// Looks up a genre (case insensitive), suggesting the closest genre for typos
+    pub fn parse(str: &str) -> Result<Genre, String> {
+        if let Some(genre) = GENRES.iter().find(|genre| genre.eq_ignore_ascii_case(str)) {
+            return Ok(Genre(genre));
+        }
+
+        // Only suggest genres that are reasonably close
+        let suggestion = GENRES
+            .iter()
+            .map(|genre| (edit_distance(genre, str), genre))
+            .filter(|(distance, genre)| *distance <= genre.len() / 3 + 1)
+            .min_by_key(|(distance, _)| *distance);
+        Err(match suggestion {
+            Some((_, genre)) => format!(
+                "{} is not a known genre, did you mean {}? (see --list-genres)",
+                str, genre
+            ),
+            None => format!("{} is not a known genre (see --list-genres)", str),
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
@@ -1,8 +1,8 @@
 use serde::Deserialize;
 
-use super::track::Track;
-use super::{get_api, APP_NAME};
+use super::{get_api, get_paginated, get_tracks_paginated, ListItem, APP_NAME, WORKERS};
 use super::{OrderedTrack, TrackGroup};
+use crate::utils::parallel_map;
 
 #[derive(Deserialize)]
 struct Playlist {
@@ -10,52 +10,96 @@ struct Playlist {
     pub playlist_name: String,
 }
 
-#[derive(Deserialize)]
-pub struct PlaylistResponse {
-    data: Vec<Playlist>,
+impl ListItem for Playlist {
+    fn id(&self) -> &str {
+        &self.id
+    }
 }
 
-#[derive(Deserialize)]
-struct PlaylistTracksResponse {
-    data: Vec<Track>,
+impl Playlist {
+    fn track_group(self, api: &str, limit: Option<usize>) -> Result<TrackGroup, String> {
+        let playlist_tracks_url = format!("{}playlists/{}/tracks", api, self.id);
+        let playlist_tracks = get_tracks_paginated(
+            &playlist_tracks_url,
+            &[],
+            limit,
+            &format!("the tracks of playlist {}", self.playlist_name),
+        )?;
+        Ok(TrackGroup {
+            tracks: playlist_tracks
+                .into_iter()
+                .enumerate()
+                .map(|(i, track)| OrderedTrack {
+                    index: i + 1,
+                    track,
+                    origin: None,
+                })
+                .collect(),
+            name: self.playlist_name,
+        })
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub struct PlaylistResponse {
+    data: Vec<Playlist>,
 }
 
 impl PlaylistResponse {
-    pub fn track_groups(self) -> Vec<TrackGroup> {
-        let api = get_api();
+    /// Fetches the playlists of a list endpoint page by page
+    pub fn get_paginated(url: &str, description: &str) -> Result<PlaylistResponse, String> {
+        Ok(PlaylistResponse {
+            data: get_paginated(url, &[], None, description, Some)?,
+        })
+    }
+
+    pub fn append(&mut self, other: PlaylistResponse) {
+        self.data.extend(other.data);
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.data.truncate(len);
+    }
+
+    pub fn track_groups(self, limit: Option<usize>) -> Result<Vec<TrackGroup>, String> {
+        let api = get_api()?;
+        parallel_map(self.data, WORKERS, |playlist| {
+            playlist.track_group(&api, limit)
+        })
+        .into_iter()
+        .collect()
+    }
+
+    /// Track groups of the playlists with the limit capping the tracks of all playlists together
+    ///
+    /// The playlists are fetched one after another until the limit is reached.
+    pub fn capped_track_groups(self, limit: usize) -> Result<Vec<TrackGroup>, String> {
+        let api = get_api()?;
         let mut track_groups = Vec::new();
-        for playlist in self.data.into_iter() {
-            let playlist_tracks_url = format!("{}playlists/{}/tracks", api, playlist.id);
-            let playlist_tracks_response: PlaylistTracksResponse = ureq::get(&playlist_tracks_url)
-                .query("app_name", APP_NAME)
-                .call()
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "Unable to execute GET request for playlist {}",
-                        playlist.playlist_name
-                    )
-                })
-                .into_json()
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "Unable to deserialize the tracks for playlist {}",
-                        playlist.playlist_name
-                    )
-                });
-            track_groups.push(TrackGroup {
-                tracks: playlist_tracks_response
-                    .data
-                    .into_iter()
-                    .enumerate()
-                    .map(|(i, track)| OrderedTrack {
-                        index: i + 1,
-                        track,
-                    })
-                    .collect(),
-                name: playlist.playlist_name,
-            });
+        let mut remaining = limit;
+        for playlist in self.data {
+            if remaining == 0 {
+                break;
+            }
+            let track_group = playlist.track_group(&api, Some(remaining))?;
+            remaining -= track_group.tracks.len();
+            if !track_group.tracks.is_empty() {
+                track_groups.push(track_group);
+            }
         }
-
-        track_groups
+        Ok(track_groups)
     }
 }
+
+/// Playlist by id
+pub fn get_playlist(id: &str, limit: Option<usize>) -> Result<Vec<TrackGroup>, String> {
+    let api = get_api()?;
+    let playlist_url = format!("{}playlists/{}", api, id);
+    let playlist_response: PlaylistResponse = ureq::get(&playlist_url)
+        .query("app_name", APP_NAME)
+        .call()
+        .map_err(|_| format!("Unable to execute GET request for playlist {}", id))?
+        .into_json()
+        .map_err(|_| format!("Unable to deserialize playlist {}", id))?;
+    playlist_response.track_groups(limit)
+}
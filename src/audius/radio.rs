@@ -0,0 +1,182 @@
+use super::genre::Genre;
+use super::track::{Track, TrackResponse};
+use super::trending::{get_trending, TimeRange};
+use super::user::User;
+use super::{get_api, OrderedTrack, TrackGroup, APP_NAME};
+use std::collections::{HashSet, VecDeque};
+
+/// Number of tracks fetched for each batch
+const BATCH_SIZE: usize = 10;
+
+/// Number of tracks fetched from each related user
+const TRACKS_PER_USER: usize = 10;
+
+/// Number of reposts checked for related users
+const REPOSTS_PER_USER: usize = 20;
+
+/// Number of trending tracks fetched for each time range
+const TRENDING_LIMIT: usize = 50;
+
+/// Endless supply of tracks related to a seed track
+///
+/// Related tracks are tracks by the same user, tracks of the same genre in trending and tracks by
+/// users the artist reposts. The reposts of these users are followed as well, so the radio keeps
+/// spreading out until nothing new is left. A track is never handed out twice.
+pub struct Radio {
+    seed: Track,
+    /// Ids of all tracks handed out so far
+    seen: HashSet<String>,
+    /// Users whose tracks haven't been fetched yet, starting with the artist of the seed track
+    users: VecDeque<User>,
+    /// Ids of all users that have been added to the users to visit
+    known_users: HashSet<String>,
+    user_tracks: VecDeque<Track>,
+    genre_tracks: VecDeque<Track>,
+    /// Genre of the seed track (if known to Audius)
+    genre: Option<Genre>,
+    /// Time ranges of the trending tracks that haven't been fetched yet
+    trending_times: VecDeque<TimeRange>,
+    /// Rank of the last track handed out
+    rank: usize,
+}
+
+impl Radio {
+    /// Starts a radio from the track behind the given URL
+    pub fn start(url: &str) -> Result<Radio, String> {
+        let api = get_api()?;
+        let seed: TrackResponse = ureq::get(format!("{}resolve", api).as_str())
+            .query("app_name", APP_NAME)
+            .query("url", url)
+            .call()
+            .map_err(|_| format!("Unable to execute GET request for {}", url))?
+            .into_json()
+            .map_err(|_| format!("{} is not a track", url))?;
+        Ok(Radio::new(seed.data))
+    }
+
+    pub fn new(seed: Track) -> Radio {
+        let genre = seed
+            .genre
+            .as_deref()
+            .and_then(|genre| Genre::parse(genre).ok());
+        let trending_times = match genre {
+            Some(_) => VecDeque::from([
+                TimeRange::Week,
+                TimeRange::Month,
+                TimeRange::Year,
+                TimeRange::AllTime,
+            ]),
+            None => VecDeque::new(),
+        };
+
+        Radio {
+            users: VecDeque::from([seed.user.clone()]),
+            known_users: HashSet::from([seed.user.id.clone()]),
+            seen: HashSet::new(),
+            user_tracks: VecDeque::new(),
+            genre_tracks: VecDeque::new(),
+            genre,
+            trending_times,
+            rank: 0,
+            seed,
+        }
+    }
+
+    /// Fetches the next tracks (starting with the seed track itself)
+    ///
+    /// An empty group is returned once there are no related tracks left. An error is only
+    /// returned if no tracks could be fetched at all, so trying again later moves on to the next
+    /// users and time ranges.
+    pub fn next_batch(&mut self) -> Result<TrackGroup, String> {
+        let mut tracks = Vec::with_capacity(BATCH_SIZE);
+        if self.seen.insert(self.seed.id.clone()) {
+            tracks.push(self.seed.clone());
+        }
+
+        // Alternate between tracks of related users and tracks of the same genre
+        let mut error = None;
+        while tracks.len() < BATCH_SIZE {
+            let len = tracks.len();
+            for next_track in [Radio::next_user_track, Radio::next_genre_track] {
+                match next_track(self) {
+                    Ok(Some(track)) => tracks.push(track),
+                    Ok(None) => {}
+                    Err(err) => error = Some(err),
+                }
+            }
+            if tracks.len() == len {
+                break;
+            }
+        }
+        tracks.truncate(BATCH_SIZE);
+
+        // The source that failed is skipped, so the error only matters without any new tracks
+        if tracks.is_empty() {
+            if let Some(err) = error {
+                return Err(err);
+            }
+        }
+
+        Ok(TrackGroup {
+            tracks: tracks
+                .into_iter()
+                .map(|track| {
+                    self.rank += 1;
+                    OrderedTrack {
+                        index: self.rank,
+                        track,
+                        origin: None,
+                    }
+                })
+                .collect(),
+            name: format!("Radio: {}", self.seed.title),
+        })
+    }
+
+    fn next_user_track(&mut self) -> Result<Option<Track>, String> {
+        loop {
+            while let Some(track) = self.user_tracks.pop_front() {
+                if self.seen.insert(track.id.clone()) {
+                    return Ok(Some(track));
+                }
+            }
+            match self.users.pop_front() {
+                Some(user) => self.visit(&user)?,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn next_genre_track(&mut self) -> Result<Option<Track>, String> {
+        loop {
+            while let Some(track) = self.genre_tracks.pop_front() {
+                if self.seen.insert(track.id.clone()) {
+                    return Ok(Some(track));
+                }
+            }
+            match self.trending_times.pop_front() {
+                Some(time) => {
+                    let trending = get_trending(self.genre, Some(time), Some(TRENDING_LIMIT))?;
+                    self.genre_tracks
+                        .extend(trending.tracks.into_iter().map(|t| t.track));
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Fetches the tracks of a user and remembers the users they repost
+    fn visit(&mut self, user: &User) -> Result<(), String> {
+        let tracks = user.tracks_track_group(Some(TRACKS_PER_USER))?.tracks;
+        self.user_tracks.extend(tracks.into_iter().map(|t| t.track));
+
+        for repost in user.reposts_track_group(Some(REPOSTS_PER_USER))?.tracks {
+            let reposted_user = repost.track.user;
+            if self.known_users.insert(reposted_user.id.clone()) {
+                self.users.push_back(reposted_user);
+            }
+        }
+
+        Ok(())
+    }
+}
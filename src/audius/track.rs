@@ -2,16 +2,41 @@ use std::io::Read;
 
 use super::user::User;
 use super::OrderedTrack;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::{get_api, TrackGroup, APP_NAME};
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Track {
-    id: String,
+    pub id: String,
     pub title: String,
     pub user: User,
     pub duration: u32,
+    pub genre: Option<String>,
+    pub mood: Option<String>,
+    /// Comma separated list of tags
+    pub tags: Option<String>,
+    pub release_date: Option<String>,
+    pub artwork: Option<Artwork>,
+    pub description: Option<String>,
+    pub permalink: Option<String>,
+    pub is_streamable: Option<bool>,
+    #[serde(default)]
+    pub play_count: u64,
+    #[serde(default)]
+    pub repost_count: u64,
+    #[serde(default)]
+    pub favorite_count: u64,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Artwork {
+    #[serde(rename = "150x150")]
+    pub small: Option<String>,
+    #[serde(rename = "480x480")]
+    pub medium: Option<String>,
+    #[serde(rename = "1000x1000")]
+    pub large: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -34,6 +59,7 @@ impl TracksResponse {
                 .map(|(i, track)| OrderedTrack {
                     index: i + 1,
                     track,
+                    origin: None,
                 })
                 .collect(),
             name: "Single track".to_string(),
@@ -41,19 +67,75 @@ impl TracksResponse {
     }
 }
 
+pub fn get_track(id: &str) -> Result<Track, String> {
+    let api = get_api()?;
+    let track_url = format!("{}tracks/{}", api, id);
+    let track_response: TrackResponse = ureq::get(&track_url)
+        .query("app_name", APP_NAME)
+        .call()
+        .map_err(|_| format!("Unable to execute GET request for track {}", id))?
+        .into_json()
+        .map_err(|_| format!("Unable to deserialize track {}", id))?;
+    Ok(track_response.data)
+}
+
 impl Track {
-    pub fn get_stream(&self) -> Result<Vec<u8>, ureq::Error> {
-        let api = get_api();
+    pub fn get_stream(&self) -> Result<Vec<u8>, String> {
+        let api = get_api()?;
 
         // Get stream
         let stream_url = api + "tracks/" + &self.id + "/stream";
-        let resp = ureq::get(&stream_url).query("app_name", APP_NAME).call()?;
+        let resp = ureq::get(&stream_url)
+            .query("app_name", APP_NAME)
+            .call()
+            .map_err(|e| e.to_string())?;
         let mut bytes = Vec::with_capacity(self.duration as usize * 320 / 8);
-        resp.into_reader().read_to_end(&mut bytes)?;
+        resp.into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(|e| e.to_string())?;
         Ok(bytes)
     }
 
     pub fn get_duration(&self) -> String {
         format!("{}:{:0>2}", self.duration / 60, self.duration % 60)
     }
+
+    pub fn get_tags(&self) -> Vec<&str> {
+        self.tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    /// URL of the largest available artwork
+    pub fn get_artwork_url(&self) -> Option<&str> {
+        let artwork = self.artwork.as_ref()?;
+        artwork
+            .large
+            .as_deref()
+            .or(artwork.medium.as_deref())
+            .or(artwork.small.as_deref())
+    }
+
+    /// First line of the description, shortened to the given number of characters
+    pub fn get_short_description(&self, max_chars: usize) -> Option<String> {
+        let line = self.description.as_deref()?.lines().next()?.trim();
+        if line.is_empty() {
+            None
+        } else if line.chars().count() > max_chars {
+            let shortened: String = line.chars().take(max_chars - 1).collect();
+            Some(format!("{}…", shortened.trim_end()))
+        } else {
+            Some(line.to_string())
+        }
+    }
+
+    pub fn get_url(&self) -> Option<String> {
+        self.permalink
+            .as_ref()
+            .map(|permalink| format!("https://audius.co{}", permalink))
+    }
 }
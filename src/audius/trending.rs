@@ -1,47 +1,122 @@
-use super::track::Track;
-use super::{get_api, OrderedTrack, TrackGroup, APP_NAME};
-use serde::Deserialize;
+use super::genre::Genre;
+use super::playlist::PlaylistResponse;
+use super::{get_api, get_tracks_paginated, OrderedTrack, TrackGroup, APP_NAME};
 
-#[derive(Deserialize)]
-struct TrendingResponse {
-    data: Vec<Track>,
+/// Time range for the trending tracks and playlists
+#[derive(Clone, Copy)]
+pub enum TimeRange {
+    Week,
+    Month,
+    Year,
+    AllTime,
 }
 
-pub fn get_trending(genre: &str, time: &str) -> TrackGroup {
+impl TimeRange {
+    pub fn parse(str: &str) -> Result<TimeRange, String> {
+        match str {
+            "week" => Ok(TimeRange::Week),
+            "month" => Ok(TimeRange::Month),
+            "year" => Ok(TimeRange::Year),
+            "allTime" => Ok(TimeRange::AllTime),
+            _ => Err(str.to_owned()
+                + " is not a valid time range [possible values: week, month, year, allTime]"),
+        }
+    }
+
+    fn query_value(&self) -> &str {
+        match self {
+            TimeRange::Week => "week",
+            TimeRange::Month => "month",
+            TimeRange::Year => "year",
+            TimeRange::AllTime => "allTime",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            TimeRange::Week => "this week",
+            TimeRange::Month => "this month",
+            TimeRange::Year => "this year",
+            TimeRange::AllTime => "all time",
+        }
+    }
+}
+
+pub fn get_trending(
+    genre: Option<Genre>,
+    time: Option<TimeRange>,
+    limit: Option<usize>,
+) -> Result<TrackGroup, String> {
     // Select API endpoint
-    let api = get_api();
+    let api = get_api()?;
 
     // Get trending tracks
     let trending_url = format!("{}tracks/trending", api);
-    let mut request = ureq::get(&trending_url).query("app_name", APP_NAME);
-    if !genre.is_empty() {
-        request = request.query("genre", genre);
+    let mut query = Vec::new();
+    if let Some(genre) = genre {
+        query.push(("genre", genre.name()));
     }
-    if !time.is_empty() {
-        request = request.query("time", time);
+    if let Some(time) = &time {
+        query.push(("time", time.query_value()));
     }
-    let trending_res: TrendingResponse = request
-        .call()
-        .expect("Unable to execute GET request for list of trending tracks")
-        .into_json()
-        .expect("Unable to deserialize the list of trending tracks");
+    let trending_tracks =
+        get_tracks_paginated(&trending_url, &query, limit, "the list of trending tracks")?;
 
     // Enrich with the track's rank
-    let mut trending_tracks = Vec::with_capacity(100);
-    let mut rank = 1;
-    for track in trending_res.data {
-        trending_tracks.push(OrderedTrack { track, index: rank });
-        rank += 1;
-    }
+    let trending_tracks = trending_tracks
+        .into_iter()
+        .enumerate()
+        .map(|(i, track)| OrderedTrack {
+            index: i + 1,
+            track,
+            origin: None,
+        })
+        .collect();
 
-    let name = match time {
-        "month" => String::from("Trending tracks of this month"),
-        "allTime" => String::from("Trending tracks of all time"),
-        _ => String::from("Trending tracks of this week"),
+    // The API defaults to the trending tracks of this week
+    let time = time.unwrap_or(TimeRange::Week).description().to_string();
+    let name = match genre {
+        Some(genre) => format!("Trending {} tracks of {}", genre.name(), time),
+        None => format!("Trending tracks of {}", time),
     };
 
-    TrackGroup {
+    Ok(TrackGroup {
         tracks: trending_tracks,
         name,
+    })
+}
+
+/// Trending playlists, each playlist is returned as a separate group
+pub fn get_trending_playlists(
+    time: Option<TimeRange>,
+    limit: Option<usize>,
+) -> Result<Vec<TrackGroup>, String> {
+    let api = get_api()?;
+
+    let trending_url = format!("{}playlists/trending", api);
+    let mut request = ureq::get(&trending_url).query("app_name", APP_NAME);
+    if let Some(time) = time {
+        request = request.query("time", time.query_value());
     }
+    if let Some(limit) = limit {
+        request = request.query("limit", &limit.to_string());
+    }
+    let mut trending_res: PlaylistResponse = request
+        .call()
+        .map_err(|_| "Unable to execute GET request for list of trending playlists".to_string())?
+        .into_json()
+        .map_err(|_| "Unable to deserialize the list of trending playlists".to_string())?;
+
+    // Only fetch the tracks of the playlists to be played
+    if let Some(limit) = limit {
+        trending_res.truncate(limit);
+    }
+
+    // Enrich with the playlist's rank
+    let mut track_groups = trending_res.track_groups(None)?;
+    for (i, group) in track_groups.iter_mut().enumerate() {
+        group.name = format!("#{} {}", i + 1, group.name);
+    }
+
+    Ok(track_groups)
 }
@@ -1,10 +1,16 @@
-use super::track::TracksResponse;
-use super::{get_api, OrderedTrack, TrackGroup, APP_NAME};
-use serde::Deserialize;
+use super::playlist::PlaylistResponse;
+use super::track::{get_track, Track};
+use super::{
+    get_api, get_paginated, get_tracks_paginated, OrderedTrack, TrackGroup, APP_NAME, WORKERS,
+};
+use crate::utils::parallel_map;
+use serde::{Deserialize, Serialize};
+use ureq::serde_json::Value;
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct User {
-    id: String,
+    pub id: String,
+    pub handle: String,
     pub name: String,
 }
 
@@ -13,32 +19,137 @@ pub struct UserResponse {
     pub data: User,
 }
 
-impl UserResponse {
-    pub fn track_group(self) -> TrackGroup {
-        let api = get_api();
-        let tracks_url = format!("{}users/{}/tracks", api, self.data.id);
-        let tracks_response: TracksResponse = ureq::get(&tracks_url)
+#[derive(Deserialize)]
+struct Favorite {
+    favorite_item_id: String,
+    favorite_type: String,
+}
+
+#[derive(Deserialize)]
+struct FavoritesResponse {
+    data: Vec<Favorite>,
+}
+
+#[derive(Deserialize)]
+struct Repost {
+    item_type: String,
+    item: Value,
+}
+
+pub fn get_user(handle: &str) -> Result<User, String> {
+    let api = get_api()?;
+    let user_url = format!("{}users/handle/{}", api, handle);
+    let user_response: UserResponse = ureq::get(&user_url)
+        .query("app_name", APP_NAME)
+        .call()
+        .map_err(|_| format!("Unable to execute GET request for user {}", handle))?
+        .into_json()
+        .map_err(|_| format!("Unable to deserialize user {}", handle))?;
+    Ok(user_response.data)
+}
+
+impl User {
+    /// Tracks uploaded by the user
+    pub fn tracks_track_group(&self, limit: Option<usize>) -> Result<TrackGroup, String> {
+        let api = get_api()?;
+        let tracks_url = format!("{}users/{}/tracks", api, self.id);
+        let tracks = get_tracks_paginated(
+            &tracks_url,
+            &[],
+            limit,
+            &format!("the tracks of user {}", self.name),
+        )?;
+        Ok(track_group(tracks, self.name.clone()))
+    }
+
+    /// Tracks favorited by the user
+    pub fn favorites_track_group(&self, limit: Option<usize>) -> Result<TrackGroup, String> {
+        let api = get_api()?;
+        let favorites_url = format!("{}users/{}/favorites", api, self.id);
+        let favorites_response: FavoritesResponse = ureq::get(&favorites_url)
             .query("app_name", APP_NAME)
             .call()
-            .unwrap_or_else(|_| panic!("Unable to execute GET request for user {}", self.data.name))
-            .into_json()
-            .unwrap_or_else(|_| {
-                panic!(
-                    "Unable to deserialize the tracks for user {}",
-                    self.data.name
+            .map_err(|_| {
+                format!(
+                    "Unable to execute GET request for favorites of {}",
+                    self.name
                 )
-            });
-        TrackGroup {
-            tracks: tracks_response
-                .data
-                .into_iter()
-                .enumerate()
-                .map(|(i, track)| OrderedTrack {
-                    index: i + 1,
-                    track,
-                })
-                .collect(),
-            name: self.data.name,
+            })?
+            .into_json()
+            .map_err(|_| format!("Unable to deserialize the favorites of {}", self.name))?;
+
+        // Favorites only contain the id, so the tracks need to be fetched separately
+        let favorites = favorites_response
+            .data
+            .into_iter()
+            .filter(|favorite| favorite.favorite_type.ends_with("track"))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        let tracks = parallel_map(favorites, WORKERS, |favorite| {
+            get_track(&favorite.favorite_item_id).ok()
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+        Ok(track_group(tracks, format!("Favorites of {}", self.name)))
+    }
+
+    /// Playlists and albums of the user, each one as a separate group
+    ///
+    /// The limit caps the number of tracks across all playlists and albums.
+    pub fn playlists_track_groups(&self, limit: Option<usize>) -> Result<Vec<TrackGroup>, String> {
+        let api = get_api()?;
+        let mut playlists = PlaylistResponse::default();
+        for kind in ["playlists", "albums"] {
+            let playlists_url = format!("{}users/{}/{}", api, self.id, kind);
+            playlists.append(PlaylistResponse::get_paginated(
+                &playlists_url,
+                &format!("the {} of {}", kind, self.name),
+            )?);
         }
+
+        // The limit applies to all playlists together
+        match limit {
+            Some(limit) => playlists.capped_track_groups(limit),
+            None => playlists.track_groups(None),
+        }
+    }
+
+    /// Tracks reposted by the user (reposted playlists are ignored)
+    pub fn reposts_track_group(&self, limit: Option<usize>) -> Result<TrackGroup, String> {
+        let api = get_api()?;
+        let reposts_url = format!("{}users/{}/reposts", api, self.id);
+        let tracks = get_paginated(
+            &reposts_url,
+            &[],
+            limit,
+            &format!("the reposts of {}", self.name),
+            |repost: Repost| match repost.item_type.as_str() {
+                "track" => ureq::serde_json::from_value(repost.item).ok(),
+                _ => None,
+            },
+        )?;
+        Ok(track_group(tracks, format!("Reposts of {}", self.name)))
+    }
+}
+
+fn track_group(tracks: Vec<Track>, name: String) -> TrackGroup {
+    TrackGroup {
+        tracks: tracks
+            .into_iter()
+            .enumerate()
+            .map(|(i, track)| OrderedTrack {
+                index: i + 1,
+                track,
+                origin: None,
+            })
+            .collect(),
+        name,
+    }
+}
+
+impl UserResponse {
+    pub fn track_group(self, limit: Option<usize>) -> Result<TrackGroup, String> {
+        self.data.tracks_track_group(limit)
     }
 }
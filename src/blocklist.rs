@@ -0,0 +1,72 @@
+use crate::audius::track::Track;
+use crate::utils::config_dir;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+const BLOCKLIST_FILE: &str = "blocklist";
+
+/// Users and tracks that should never be played
+///
+/// The blocklist file contains one entry per line, either `user:<handle or id>` or
+/// `track:<id>`. Everything after a `#` is treated as a comment.
+#[derive(Default)]
+pub struct Blocklist {
+    users: HashSet<String>,
+    tracks: HashSet<String>,
+}
+
+impl Blocklist {
+    pub fn load() -> Blocklist {
+        let mut blocklist = Blocklist::default();
+        let content = blocklist_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        for line in content.lines() {
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            if let Some(user) = entry.strip_prefix("user:") {
+                blocklist.users.insert(user.trim().to_string());
+            } else if let Some(track) = entry.strip_prefix("track:") {
+                blocklist.tracks.insert(track.trim().to_string());
+            }
+        }
+
+        blocklist
+    }
+
+    pub fn is_blocked(&self, track: &Track) -> bool {
+        self.tracks.contains(&track.id)
+            || self.users.contains(&track.user.id)
+            || self
+                .users
+                .iter()
+                .any(|user| user.eq_ignore_ascii_case(&track.user.handle))
+    }
+
+    /// Blocks the track and appends it to the blocklist file
+    pub fn block_track(&mut self, track: &Track) -> Result<(), String> {
+        self.tracks.insert(track.id.clone());
+
+        let path = blocklist_path().ok_or("Unable to determine the blocklist location")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        writeln!(
+            file,
+            "track:{} # {} - {}",
+            track.id, track.user.name, track.title
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+fn blocklist_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(BLOCKLIST_FILE))
+}
@@ -0,0 +1,259 @@
+use crate::playable::Playable;
+use crate::queue::{Placement, QueueCommand};
+use crate::ui::Event;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for the player to report its status
+const STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Command sent to a running instance (one JSON object per line)
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+enum Request {
+    PlayPause,
+    Next,
+    Previous,
+    Volume {
+        change: VolumeChange,
+    },
+    /// Sets the linear volume (between 0.0 and 1.0)
+    SetVolume {
+        volume: f32,
+    },
+    Enqueue {
+        playable: String,
+        placement: Option<Placement>,
+    },
+    Status,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum VolumeChange {
+    Up,
+    Down,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Response {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<Status>,
+}
+
+impl Response {
+    fn ok(status: Option<Status>) -> Response {
+        Response {
+            ok: true,
+            error: None,
+            status,
+        }
+    }
+
+    fn error(error: String) -> Response {
+        Response {
+            ok: false,
+            error: Some(error),
+            status: None,
+        }
+    }
+}
+
+/// State of the player reported to clients
+#[derive(Deserialize, Serialize)]
+pub struct Status {
+    pub playing: bool,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub url: Option<String>,
+    /// Position within the current track in seconds
+    pub position: u64,
+    /// Duration of the current track in seconds
+    pub duration: u32,
+    /// Number of tracks after the current one
+    pub upcoming: usize,
+    /// Linear volume (between 0.0 and 1.0)
+    pub volume: f32,
+}
+
+/// Location of the control socket (in the runtime directory if there is one)
+fn socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("halkara.sock"),
+        _ => env::temp_dir().join(format!(
+            "halkara-{}.sock",
+            env::var("USER").unwrap_or_default()
+        )),
+    }
+}
+
+/// Accepts commands on the control socket and translates them into events
+pub fn listen(event_sender: Sender<Event>) -> Result<(), String> {
+    let path = socket_path();
+    if UnixStream::connect(&path).is_ok() {
+        return Err(format!(
+            "Another instance is already listening on {}",
+            path.display()
+        ));
+    }
+
+    // Remove the socket left behind by an instance that didn't quit properly
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .map_err(|e| format!("Unable to listen on {}: {}", path.display(), e))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let event_sender = event_sender.clone();
+            thread::spawn(move || handle_connection(stream, event_sender));
+        }
+    });
+
+    Ok(())
+}
+
+/// Removes the control socket
+pub fn close() {
+    let _ = fs::remove_file(socket_path());
+}
+
+fn handle_connection(stream: UnixStream, event_sender: Sender<Event>) {
+    let reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(_) => return,
+    };
+    let mut writer = stream;
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let response = match ureq::serde_json::from_str(&line) {
+            Ok(request) => handle_request(request, &event_sender),
+            Err(err) => Response::error(format!("Invalid request: {}", err)),
+        };
+        let mut json = ureq::serde_json::to_string(&response).expect("Serializing response");
+        json.push('\n');
+        if writer.write_all(json.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+fn handle_request(request: Request, event_sender: &Sender<Event>) -> Response {
+    let event = match request {
+        Request::PlayPause => Event::Pause,
+        Request::Next => Event::Next,
+        Request::Previous => Event::Previous,
+        Request::SetVolume { volume } => Event::SetVolume(volume),
+        Request::Volume {
+            change: VolumeChange::Up,
+        } => Event::VolumeUp,
+        Request::Volume {
+            change: VolumeChange::Down,
+        } => Event::VolumeDown,
+        Request::Enqueue {
+            playable,
+            placement,
+        } => match Playable::parse(&playable) {
+            Ok(playable) => Event::Queue(QueueCommand::Add(
+                playable,
+                placement.unwrap_or(Placement::End),
+            )),
+            Err(err) => return Response::error(err),
+        },
+        Request::Status => {
+            let (status_sender, status_receiver) = channel();
+            if event_sender.send(Event::Status(status_sender)).is_err() {
+                return Response::error(String::from("Halkara is quitting"));
+            }
+            return match status_receiver.recv_timeout(STATUS_TIMEOUT) {
+                Ok(status) => Response::ok(Some(status)),
+                Err(_) => Response::error(String::from("The player didn't report its status")),
+            };
+        }
+    };
+
+    match event_sender.send(event) {
+        Ok(_) => Response::ok(None),
+        Err(_) => Response::error(String::from("Halkara is quitting")),
+    }
+}
+
+/// Sends a command to the running instance and prints the status if requested
+pub fn client(args: &[String]) -> Result<(), String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let request = match args[..] {
+        ["play-pause"] => Request::PlayPause,
+        ["next"] => Request::Next,
+        ["previous"] => Request::Previous,
+        ["volume", "up"] => Request::Volume {
+            change: VolumeChange::Up,
+        },
+        ["volume", "down"] => Request::Volume {
+            change: VolumeChange::Down,
+        },
+        ["volume", volume] => Request::SetVolume {
+            volume: volume
+                .parse()
+                .map_err(|_| volume.to_owned() + " is not a valid volume")?,
+        },
+        ["enqueue", playable] => Request::Enqueue {
+            playable: playable.to_string(),
+            placement: None,
+        },
+        ["enqueue", playable, placement] => Request::Enqueue {
+            playable: playable.to_string(),
+            placement: Some(match placement {
+                "now" => Placement::Now,
+                "next" => Placement::Next,
+                "end" => Placement::End,
+                _ => return Err(placement.to_owned() + " is not a valid placement"),
+            }),
+        },
+        ["status"] => Request::Status,
+        _ => {
+            return Err(String::from(
+                "Usage: halkara ctl <play-pause|next|previous|volume <up|down|level>|enqueue <playable> [now|next|end]|status>",
+            ))
+        }
+    };
+
+    let path = socket_path();
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|_| format!("Unable to connect to Halkara at {}", path.display()))?;
+    let mut json = ureq::serde_json::to_string(&request).expect("Serializing request");
+    json.push('\n');
+    stream
+        .write_all(json.as_bytes())
+        .map_err(|e| format!("Unable to send the command: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("Unable to receive the response: {}", e))?;
+    let response: Response = ureq::serde_json::from_str(&line)
+        .map_err(|_| String::from("Unable to deserialize the response"))?;
+    if let Some(error) = response.error {
+        return Err(error);
+    }
+    if let Some(status) = response.status {
+        println!(
+            "{}",
+            ureq::serde_json::to_string(&status).expect("Serializing status")
+        );
+    }
+
+    Ok(())
+}
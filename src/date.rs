@@ -0,0 +1,53 @@
+use std::fmt;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Calendar date without time and time zone
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        if (1..=12).contains(&month) && (1..=31).contains(&day) {
+            Some(Date { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    /// Parses dates like 2022-05-31 (anything after the day is ignored)
+    pub fn parse_iso(str: &str) -> Option<Date> {
+        let mut parts = str.get(0..10)?.split('-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Date::new(year, month, day)
+    }
+
+    /// Parses the release date format of the Audius API
+    ///
+    /// The API either returns ISO 8601 dates or the JavaScript format like
+    /// "Tue May 31 2022 00:00:00 GMT-0700".
+    pub fn parse_release_date(str: &str) -> Option<Date> {
+        Date::parse_iso(str).or_else(|| {
+            let mut parts = str.split_whitespace().skip(1);
+            let month = parts.next()?;
+            let month = MONTHS.iter().position(|m| *m == month)? as u8 + 1;
+            let day = parts.next()?.parse().ok()?;
+            let year = parts.next()?.parse().ok()?;
+            Date::new(year, month, day)
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{:0>2}-{:0>2}", self.year, self.month, self.day)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::audius::track::Track;
+use crate::audius::{OrderedTrack, TrackGroup};
+use crate::utils::{load_data, save_data};
+use serde::{Deserialize, Serialize};
+
+const FAVORITES_FILE: &str = "favorites.json";
+
+/// Locally starred tracks (no Audius account required)
+#[derive(Default, Deserialize, Serialize)]
+pub struct Favorites {
+    tracks: Vec<Track>,
+    /// Why the favorites file couldn't be loaded (it must not be overwritten then)
+    #[serde(skip)]
+    load_error: Option<String>,
+}
+
+impl Favorites {
+    pub fn load() -> Favorites {
+        load_data(FAVORITES_FILE).map_or_else(
+            |err| Favorites {
+                load_error: Some(err),
+                ..Default::default()
+            },
+            Option::unwrap_or_default,
+        )
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        match &self.load_error {
+            Some(err) => Err(format!("{} (not overwriting it)", err)),
+            None => save_data(FAVORITES_FILE, self),
+        }
+    }
+
+    /// Adds the track to the favorites (returns false if it has been added before)
+    pub fn add(&mut self, track: &Track) -> bool {
+        if self.tracks.iter().any(|t| t.id == track.id) {
+            false
+        } else {
+            self.tracks.push(track.clone());
+            true
+        }
+    }
+
+    pub fn track_group(self) -> Result<TrackGroup, String> {
+        if let Some(err) = self.load_error {
+            return Err(err);
+        }
+
+        Ok(TrackGroup {
+            tracks: self
+                .tracks
+                .into_iter()
+                .enumerate()
+                .map(|(i, track)| OrderedTrack {
+                    index: i + 1,
+                    track,
+                    origin: None,
+                })
+                .collect(),
+            name: "Favorites".to_string(),
+        })
+    }
+}
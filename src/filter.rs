@@ -0,0 +1,81 @@
+use crate::audius::track::Track;
+use crate::date::Date;
+use regex::Regex;
+use std::time::Duration;
+
+/// Criteria a track needs to fulfill to be played
+pub struct TrackFilter {
+    pub(crate) min_length: Option<Duration>,
+    pub(crate) max_length: Option<Duration>,
+    pub(crate) include_genres: Vec<String>,
+    pub(crate) exclude_genres: Vec<String>,
+    pub(crate) include_moods: Vec<String>,
+    pub(crate) exclude_moods: Vec<String>,
+    pub(crate) include_tags: Vec<String>,
+    pub(crate) exclude_tags: Vec<String>,
+    pub(crate) min_plays: Option<u64>,
+    pub(crate) min_favorites: Option<u64>,
+    pub(crate) released_after: Option<Date>,
+    pub(crate) released_before: Option<Date>,
+    pub(crate) title_regex: Option<Regex>,
+}
+
+impl TrackFilter {
+    pub fn matches(&self, track: &Track) -> bool {
+        self.matches_length(track)
+            && self.matches_metadata(track)
+            && self.matches_counts(track)
+            && self.matches_release_date(track)
+            && self
+                .title_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&track.title))
+    }
+
+    fn matches_length(&self, track: &Track) -> bool {
+        let duration = Duration::from_secs(track.duration as u64);
+        duration <= self.max_length.unwrap_or(Duration::from_secs(u64::MAX))
+            && duration >= self.min_length.unwrap_or(Duration::from_secs(u64::MIN))
+    }
+
+    fn matches_metadata(&self, track: &Track) -> bool {
+        let genre = track.genre.as_deref().map(|g| vec![g]).unwrap_or_default();
+        let mood = track.mood.as_deref().map(|m| vec![m]).unwrap_or_default();
+        let tags = track.get_tags();
+        matches_values(&genre, &self.include_genres, &self.exclude_genres)
+            && matches_values(&mood, &self.include_moods, &self.exclude_moods)
+            && matches_values(&tags, &self.include_tags, &self.exclude_tags)
+    }
+
+    fn matches_counts(&self, track: &Track) -> bool {
+        track.play_count >= self.min_plays.unwrap_or_default()
+            && track.favorite_count >= self.min_favorites.unwrap_or_default()
+    }
+
+    fn matches_release_date(&self, track: &Track) -> bool {
+        if self.released_after.is_none() && self.released_before.is_none() {
+            return true;
+        }
+
+        // Tracks without a (known) release date can't fulfill a date range
+        match track
+            .release_date
+            .as_deref()
+            .and_then(Date::parse_release_date)
+        {
+            Some(date) => {
+                self.released_after.is_none_or(|after| date >= after)
+                    && self.released_before.is_none_or(|before| date <= before)
+            }
+            None => false,
+        }
+    }
+}
+
+/// At least one value needs to be included (if there are inclusions) and no value may be excluded
+fn matches_values(values: &[&str], include: &[String], exclude: &[String]) -> bool {
+    let contains =
+        |list: &[String], value: &str| list.iter().any(|v| v.eq_ignore_ascii_case(value));
+    (include.is_empty() || values.iter().any(|v| contains(include, v)))
+        && !values.iter().any(|v| contains(exclude, v))
+}
@@ -0,0 +1,52 @@
+use crate::utils::{load_data, save_data};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = "history.json";
+
+/// Local play history, mapping track ids to the time they were last played
+#[derive(Default, Deserialize, Serialize)]
+pub struct History {
+    played: HashMap<String, u64>,
+    /// Why the history file couldn't be loaded (it must not be overwritten then)
+    #[serde(skip)]
+    load_error: Option<String>,
+}
+
+impl History {
+    pub fn load() -> History {
+        load_data(HISTORY_FILE).map_or_else(
+            |err| History {
+                load_error: Some(err),
+                ..Default::default()
+            },
+            Option::unwrap_or_default,
+        )
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        match &self.load_error {
+            Some(err) => Err(format!("{} (not overwriting it)", err)),
+            None => save_data(HISTORY_FILE, self),
+        }
+    }
+
+    pub fn record(&mut self, track_id: &str) {
+        self.played.insert(track_id.to_string(), now());
+    }
+
+    /// Checks if the track has been played within the given time window
+    pub fn played_within(&self, track_id: &str, window: Duration) -> bool {
+        match self.played.get(track_id) {
+            Some(timestamp) => now().saturating_sub(*timestamp) <= window.as_secs(),
+            None => false,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
@@ -1,13 +1,37 @@
-use crate::args::{is_trending, parse_trending_arg};
+use crate::args::ConsoleArgs;
+use crate::audius::radio::Radio;
+use crate::audius::track::Track;
+use crate::audius::{OrderedTrack, TrackGroup};
+use crate::blocklist::Blocklist;
+use crate::control::Status;
+use crate::favorites::Favorites;
+use crate::history::History;
+use crate::playable::Playable;
 use crate::player::Player;
+use crate::queue::{Placement, Queue, QueueCommand};
+use crate::session::{Restored, Session};
 use crate::ui::{Event, HalkaraUi, UiVariant};
-use crate::utils::shuffle_n;
-use std::sync::mpsc::channel;
+use crate::utils::{parallel_for_each, random_seed, shuffle, spread_shuffle, Rng};
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 mod args;
 mod audius;
+mod blocklist;
+mod control;
+mod date;
+mod favorites;
+mod filter;
+mod history;
+#[cfg(feature = "mpris")]
+mod mpris;
+mod playable;
 mod player;
+mod queue;
+mod session;
+mod sort;
 mod ui;
 mod utils;
 
@@ -15,61 +39,103 @@ enum PlayOrder {
     Ascending,
     Descending,
     Random,
+    Smart,
+}
+
+enum GroupMode {
+    Separate,
+    Merge,
+    Interleave,
+}
+
+enum ShuffleWeight {
+    Plays,
+    Rank,
 }
 
 fn main() {
-    let console_args = unwrap_or_return!(args::handle_args());
-
-    let mut track_groups = Vec::with_capacity(std::cmp::max(1, console_args.playables.len()));
-    if console_args.playables.is_empty() {
-        track_groups.push(audius::trending::get_trending(
-            &console_args.genre.unwrap_or_default(),
-            &console_args.time.unwrap_or_default(),
-        ));
-    } else {
-        for playable in console_args.playables {
-            if is_trending(&playable) {
-                let trending_args = parse_trending_arg(&playable);
-                track_groups.push(audius::trending::get_trending(
-                    &trending_args.genre.unwrap_or_default(),
-                    &trending_args.time.unwrap_or_default(),
-                ));
-            } else {
-                track_groups
-                    .append(&mut audius::resolve(&playable).expect("Building final playlist"));
-            }
+    // Control a running instance instead of playing
+    let env_args: Vec<String> = std::env::args().skip(1).collect();
+    if env_args.first().map(String::as_str) == Some("ctl") {
+        if let Err(err) = control::client(&env_args[1..]) {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
         }
-    };
+        return;
+    }
 
-    for group in track_groups.iter_mut() {
-        // Filter tracks
-        group.tracks.retain(|t| {
-            Duration::from_secs(t.track.duration as u64)
-                <= console_args
-                    .max_length
-                    .unwrap_or(Duration::from_secs(u64::MAX))
-        });
-        group.tracks.retain(|t| {
-            Duration::from_secs(t.track.duration as u64)
-                >= console_args
-                    .min_length
-                    .unwrap_or(Duration::from_secs(u64::MIN))
-        });
+    let mut console_args = unwrap_or_return!(args::handle_args());
+
+    let mut history = History::load();
+    let mut blocklist = Blocklist::load();
+    let mut favorites = Favorites::load();
+    let seed = console_args.seed.unwrap_or_else(random_seed);
+    let mut rng = Rng::new(seed);
+
+    let mut start_position = Duration::ZERO;
+    let radio = Arc::new(Mutex::new(None));
+    let mut queue = Queue::new(Vec::new());
+
+    // Create event channel
+    let (event_sender, event_receiver) = channel();
 
-        // Reorder tracks
-        match console_args.order {
-            PlayOrder::Descending => {
-                group.tracks.reverse();
+    // Number of playables that are still being resolved in the background
+    let mut unresolved = 0;
+    // Number of events that can still add tracks to the queue
+    let mut pending = if console_args.resume {
+        let session = match Session::load() {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                eprintln!("Error: there is no session to resume");
+                return;
             }
-            PlayOrder::Random => {
-                shuffle_n(&mut (group.tracks), 2);
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return;
+            }
+        };
+
+        // Start with the current track while the others are resolved
+        let event_sender = event_sender.clone();
+        std::thread::spawn(move || {
+            for restored in [session.restore_current(), Some(session.restore_remaining())]
+                .into_iter()
+                .flatten()
+            {
+                event_sender
+                    .send(Event::Restored(restored))
+                    .expect("Sending restored event");
             }
-            _ => {}
+        });
+        1
+    } else {
+        // Feed the queue with the playables as soon as they are resolved (keeping their order)
+        let mut playables = std::mem::take(&mut console_args.playables);
+        if playables.is_empty() {
+            playables.push(Playable::Trending {
+                genre: console_args.genre,
+                time: console_args.time,
+            });
         }
-    }
+        unresolved = playables.len();
 
-    // Create event channel
-    let (event_sender, event_receiver) = channel();
+        let event_sender = event_sender.clone();
+        let limit = console_args.limit;
+        let radio = Arc::clone(&radio);
+        std::thread::spawn(move || {
+            parallel_for_each(
+                playables,
+                audius::WORKERS,
+                |playable| resolve_playable(&playable, limit, &radio),
+                |result| {
+                    event_sender
+                        .send(Event::Resolved(result))
+                        .expect("Sending resolved event");
+                },
+            );
+        });
+        unresolved
+    };
 
     // Create player
     let player = Player::new(event_sender.clone(), console_args.volume);
@@ -95,49 +161,448 @@ fn main() {
             }
         }
     }
-    hui.start_reader(event_sender);
+    hui.start_reader(event_sender.clone());
 
     hui.setup();
 
+    let listening = match control::listen(event_sender.clone()) {
+        Ok(_) => true,
+        Err(err) => {
+            hui.error(&err);
+            false
+        }
+    };
+    #[cfg(feature = "mpris")]
+    let mpris = match mpris::Mpris::start(event_sender.clone()) {
+        Ok(mpris) => Some(mpris),
+        Err(err) => {
+            hui.error(&err);
+            None
+        }
+    };
+
+    if !console_args.resume && matches!(console_args.order, PlayOrder::Random | PlayOrder::Smart) {
+        hui.info(&format!("Seed: {}", seed));
+    }
+
     let mut quit = false;
-    for (i, group) in track_groups.iter().enumerate() {
-        for (j, track) in group.tracks.iter().enumerate() {
-            hui.display(&track_groups, i, j);
-            if let Err(err) = player.play(&track.track) {
-                hui.error(&err);
-                continue;
+    // Resolved playables waiting for the others (when merging or interleaving)
+    let mut resolved = Vec::new();
+    let mut known_tracks = HashSet::new();
+    let mut refilling = false;
+    while !quit {
+        let position = queue.advance();
+        if position.is_some() && !refilling {
+            refilling = refill_radio(&radio, &queue, &event_sender, Duration::ZERO);
+        }
+        let track = match position {
+            Some((group, track_index)) => {
+                let track = queue.track_groups[group].tracks[track_index].track.clone();
+                hui.display(&queue.track_groups, group, track_index);
+                let position = std::mem::take(&mut start_position);
+                if let Err(err) = player.play(&track, position) {
+                    hui.error(&err);
+                    continue;
+                }
+
+                history.record(&track.id);
+                if let Err(err) = history.save() {
+                    hui.error(&format!("Unable to save the play history: {}", err));
+                }
+                Some(track)
+            }
+            // Wait for the tracks that are still on their way
+            None if pending > 0 || refilling => None,
+            None => break,
+        };
+        #[cfg(feature = "mpris")]
+        if let Some(mpris) = &mpris {
+            mpris.update(track.as_ref(), &player);
+        }
+        // Tracks might have been inserted in front of the current track
+        let refresh = |hui: &dyn HalkaraUi, queue: &Queue| {
+            if let Some((group, track_index)) = position.and(queue.position()) {
+                hui.refresh(&queue.track_groups, group, track_index);
             }
+        };
 
-            // Wait for input or track end
-            loop {
-                match event_receiver.recv().expect("Receiving event") {
-                    Event::Pause => {
-                        player.pause();
+        // Wait for input or track end
+        loop {
+            match event_receiver.recv().expect("Receiving event") {
+                Event::Block => {
+                    if let Some(track) = &track {
+                        if let Err(err) = blocklist.block_track(track) {
+                            hui.error(&format!("Unable to save the blocklist: {}", err));
+                        }
+                        player.skip();
                     }
-                    Event::Quit => {
-                        quit = true;
+                }
+                Event::Enqueue(result, placement) => {
+                    pending -= 1;
+                    match result {
+                        Ok(mut track_groups) => {
+                            filter(&mut track_groups, &console_args, &history, &blocklist);
+                            for group in track_groups.iter_mut() {
+                                reorder(&mut group.tracks, &console_args, &mut rng);
+                            }
+                            let is_empty = track_groups.iter().all(|g| g.tracks.is_empty());
+                            match placement {
+                                Placement::Now | Placement::Next => queue.insert_next(track_groups),
+                                Placement::End => queue.append(track_groups),
+                            }
+                            refresh(hui.as_ref(), &queue);
+                            if matches!(placement, Placement::Now) && !is_empty {
+                                player.skip();
+                            }
+                        }
+                        Err(err) => hui.error(&err),
+                    }
+                    if track.is_none() {
                         break;
                     }
-                    Event::TrackEnd => {
+                }
+                Event::Favorite => {
+                    if let Some(track) = &track {
+                        if favorites.add(track) {
+                            if let Err(err) = favorites.save() {
+                                hui.error(&format!("Unable to save the favorites: {}", err));
+                            } else {
+                                hui.info(&format!("Added {} to the favorites", track.title));
+                            }
+                        }
+                    }
+                }
+                Event::Next => {
+                    if track.is_some() {
+                        player.skip();
+                    }
+                }
+                Event::Pause => {
+                    player.pause();
+                }
+                Event::Previous => {
+                    if track.is_some() {
+                        queue.rewind();
+                        player.skip();
+                    }
+                }
+                Event::Queue(command) => {
+                    let result = match command {
+                        QueueCommand::Add(playable, placement) => {
+                            // Resolve in the background to keep the current track playing
+                            let event_sender = event_sender.clone();
+                            let limit = console_args.limit;
+                            let radio = Arc::clone(&radio);
+                            std::thread::spawn(move || {
+                                event_sender
+                                    .send(Event::Enqueue(
+                                        resolve_playable(&playable, limit, &radio),
+                                        placement,
+                                    ))
+                                    .expect("Sending enqueue event");
+                            });
+                            pending += 1;
+                            Ok(())
+                        }
+                        QueueCommand::Clear => {
+                            queue.clear();
+                            Ok(())
+                        }
+                        QueueCommand::Move(from, to) => queue.move_track(from, to),
+                        QueueCommand::Remove(position) => queue.remove(position).map(|_| ()),
+                    };
+                    match result {
+                        Ok(_) => refresh(hui.as_ref(), &queue),
+                        Err(err) => hui.error(&err),
+                    }
+                }
+                Event::Quit => {
+                    quit = true;
+                    break;
+                }
+                Event::RadioBatch(result) => {
+                    refilling = false;
+                    match result {
+                        Ok(group) if group.tracks.is_empty() => {
+                            hui.info("The radio ran out of related tracks");
+                        }
+                        Ok(group) => {
+                            let mut track_groups = vec![group];
+                            filter(&mut track_groups, &console_args, &history, &blocklist);
+                            for group in track_groups.iter_mut() {
+                                reorder(&mut group.tracks, &console_args, &mut rng);
+                            }
+                            queue.append(track_groups);
+                            refresh(hui.as_ref(), &queue);
+                            // Everything might have been filtered out
+                            refilling = refill_radio(&radio, &queue, &event_sender, Duration::ZERO);
+                        }
+                        Err(err) => {
+                            // Try again later instead of ending the radio on a network error
+                            hui.error(&err);
+                            refilling =
+                                refill_radio(&radio, &queue, &event_sender, RADIO_RETRY_DELAY);
+                        }
+                    }
+                    if track.is_none() {
                         break;
                     }
-                    Event::VolumeUp => {
-                        player.volume_up();
+                }
+                Event::Resolved(result) => {
+                    pending -= 1;
+                    unresolved -= 1;
+                    match result {
+                        Ok(mut track_groups) => resolved.append(&mut track_groups),
+                        Err(err) => hui.error(&err),
                     }
-                    Event::VolumeDown => {
-                        player.volume_down();
+
+                    // Merging and interleaving need the tracks of all playables
+                    if matches!(console_args.group_mode, GroupMode::Separate) || unresolved == 0 {
+                        let mut track_groups = std::mem::take(&mut resolved);
+                        if !console_args.keep_duplicates {
+                            let duplicates =
+                                TrackGroup::remove_duplicates(&mut track_groups, &mut known_tracks);
+                            if duplicates > 0 {
+                                hui.info(&format!("{} duplicates removed", duplicates));
+                            }
+                        }
+                        filter(&mut track_groups, &console_args, &history, &blocklist);
+                        queue.append(arrange(track_groups, &console_args, &mut rng));
+                        refresh(hui.as_ref(), &queue);
+                    }
+                    if track.is_none() {
+                        break;
                     }
                 }
+                Event::Restored(Restored::Current(track_group, position)) => {
+                    start_position = position;
+                    queue.insert_next(vec![track_group]);
+                    refresh(hui.as_ref(), &queue);
+                    if track.is_none() {
+                        break;
+                    }
+                }
+                Event::Restored(Restored::Remaining { played, upcoming }) => {
+                    pending -= 1;
+                    queue.insert_played(played);
+                    queue.insert_next(upcoming);
+                    refresh(hui.as_ref(), &queue);
+                    if track.is_none() {
+                        break;
+                    }
+                }
+                Event::SetVolume(volume) => {
+                    player.set_volume(volume);
+                }
+                Event::Status(status_sender) => {
+                    // The client might have given up waiting already
+                    let _ = status_sender.send(Status {
+                        playing: track.is_some() && !player.is_paused(),
+                        title: track.as_ref().map(|t| t.title.clone()),
+                        artist: track.as_ref().map(|t| t.user.name.clone()),
+                        url: track.as_ref().and_then(Track::get_url),
+                        position: track.as_ref().map_or(0, |_| player.position().as_secs()),
+                        duration: track.as_ref().map_or(0, |t| t.duration),
+                        upcoming: queue.upcoming(),
+                        volume: player.volume(),
+                    });
+                }
+                Event::TrackEnd => {
+                    break;
+                }
+                Event::VolumeUp => {
+                    player.volume_up();
+                }
+                Event::VolumeDown => {
+                    player.volume_down();
+                }
             }
 
-            if quit {
-                break;
+            // Publish changes like pausing or adjusting the volume
+            #[cfg(feature = "mpris")]
+            if let Some(mpris) = &mpris {
+                mpris.update(track.as_ref(), &player);
             }
         }
-        if quit {
-            break;
+    }
+
+    // Save the session for resuming later (a finished queue can't be resumed). The previous
+    // session is kept if quitting before anything has played or while the queue is still filled.
+    if !quit {
+        Session::remove();
+    } else if pending == 0 && queue.current().is_some() {
+        if let Err(err) = Session::new(&queue, player.position()).save() {
+            hui.error(&format!("Unable to save the session: {}", err));
         }
     }
 
+    if listening {
+        control::close();
+    }
+
     hui.cleanup();
 }
+
+/// Orders the tracks of the resolved playables according to the group mode and play order
+fn arrange(
+    mut track_groups: Vec<TrackGroup>,
+    console_args: &ConsoleArgs,
+    rng: &mut Rng,
+) -> Vec<TrackGroup> {
+    // Reorder tracks (across all groups if they are merged)
+    if let GroupMode::Merge = console_args.group_mode {
+        track_groups = vec![TrackGroup::merge(track_groups)];
+    }
+    for group in track_groups.iter_mut() {
+        reorder(&mut group.tracks, console_args, rng);
+    }
+    if let GroupMode::Interleave = console_args.group_mode {
+        track_groups = vec![TrackGroup::interleave(track_groups)];
+    }
+
+    track_groups
+}
+
+/// Number of upcoming tracks below which the radio fetches more tracks
+const RADIO_THRESHOLD: usize = 3;
+
+/// Time to wait before fetching tracks from the radio again after an error
+const RADIO_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Fetches more tracks from the radio in the background if the queue is running low
+///
+/// Returns whether new tracks are on their way.
+fn refill_radio(
+    radio: &Arc<Mutex<Option<Radio>>>,
+    queue: &Queue,
+    event_sender: &Sender<Event>,
+    delay: Duration,
+) -> bool {
+    if queue.upcoming() >= RADIO_THRESHOLD || radio.lock().expect("Locking radio").is_none() {
+        return false;
+    }
+
+    let radio = Arc::clone(radio);
+    let event_sender = event_sender.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let mut radio = radio.lock().expect("Locking radio");
+        let result = match radio.as_mut() {
+            Some(station) => station.next_batch(),
+            None => Err(String::from("The radio has been stopped")),
+        };
+        // The radio is done once it runs out of related tracks
+        if matches!(&result, Ok(group) if group.tracks.is_empty()) {
+            *radio = None;
+        }
+        event_sender
+            .send(Event::RadioBatch(result))
+            .expect("Sending radio batch event");
+    });
+    true
+}
+
+/// Resolves a playable into its track groups
+///
+/// Starting a radio replaces the current one (only one radio can be played at a time).
+fn resolve_playable(
+    playable: &Playable,
+    limit: Option<usize>,
+    radio: &Mutex<Option<Radio>>,
+) -> Result<Vec<TrackGroup>, String> {
+    let mut track_groups = match playable {
+        Playable::Favorites => vec![Favorites::load().track_group()?],
+        Playable::Feed(feed) => vec![audius::feed::get_feed(feed)?],
+        Playable::Playlist(id) => audius::playlist::get_playlist(id, limit)?,
+        Playable::Radio(url) => {
+            let mut station = Radio::start(url)?;
+            let track_group = station.next_batch()?;
+            *radio.lock().expect("Locking radio") = Some(station);
+            vec![track_group]
+        }
+        Playable::Track(id) => {
+            let track = audius::track::get_track(id)?;
+            vec![TrackGroup {
+                name: track.title.clone(),
+                tracks: vec![OrderedTrack {
+                    track,
+                    index: 1,
+                    origin: None,
+                }],
+            }]
+        }
+        Playable::Trending { genre, time } => {
+            vec![audius::trending::get_trending(*genre, *time, limit)?]
+        }
+        // The limit refers to the number of playlists for the trending playlists
+        Playable::TrendingPlaylists { time } => {
+            return audius::trending::get_trending_playlists(*time, limit);
+        }
+        Playable::Url(kind, url) => audius::resolve(url, kind, limit)?,
+        Playable::User(handle) => vec![audius::user::get_user(handle)?.tracks_track_group(limit)?],
+        Playable::UserFavorites(handle) => {
+            vec![audius::user::get_user(handle)?.favorites_track_group(limit)?]
+        }
+        Playable::UserPlaylists(handle) => {
+            audius::user::get_user(handle)?.playlists_track_groups(limit)?
+        }
+        Playable::UserReposts(handle) => {
+            vec![audius::user::get_user(handle)?.reposts_track_group(limit)?]
+        }
+    };
+
+    // Not every playable supports pagination
+    if let Some(limit) = limit {
+        for group in track_groups.iter_mut() {
+            group.tracks.truncate(limit);
+        }
+    }
+
+    Ok(track_groups)
+}
+
+fn filter(
+    track_groups: &mut [TrackGroup],
+    console_args: &ConsoleArgs,
+    history: &History,
+    blocklist: &Blocklist,
+) {
+    for group in track_groups.iter_mut() {
+        group
+            .tracks
+            .retain(|t| console_args.filter.matches(&t.track));
+        if let Some(skip_played) = console_args.skip_played {
+            group
+                .tracks
+                .retain(|t| !history.played_within(&t.track.id, skip_played));
+        }
+        group.tracks.retain(|t| !blocklist.is_blocked(&t.track));
+    }
+}
+
+fn reorder(tracks: &mut Vec<OrderedTrack>, console_args: &ConsoleArgs, rng: &mut Rng) {
+    match console_args.order {
+        PlayOrder::Descending => {
+            tracks.reverse();
+        }
+        PlayOrder::Random => {
+            shuffle(tracks, rng);
+        }
+        PlayOrder::Smart => {
+            spread_shuffle(
+                tracks,
+                rng,
+                |t| t.track.user.id.clone(),
+                |t| match console_args.shuffle_weight {
+                    Some(ShuffleWeight::Plays) => t.track.play_count as f64 + 1.0,
+                    Some(ShuffleWeight::Rank) => 1.0 / t.index as f64,
+                    None => 1.0,
+                },
+            );
+        }
+        _ => {}
+    }
+    if let Some(sort) = &console_args.sort {
+        sort.apply(tracks);
+    }
+}
@@ -0,0 +1,319 @@
+use crate::audius::track::Track;
+use crate::playable::Playable;
+use crate::player::Player;
+use crate::queue::{Placement, QueueCommand};
+use crate::ui::Event;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+use zbus::interface;
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.halkara";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// Exposes the player on the session bus, so media keys and desktop widgets can control it
+pub struct Mpris {
+    connection: Connection,
+}
+
+impl Mpris {
+    pub fn start(event_sender: Sender<Event>) -> Result<Mpris, String> {
+        let connect = |name: String| {
+            Builder::session()?
+                .name(name)?
+                .serve_at(
+                    OBJECT_PATH,
+                    Root {
+                        event_sender: event_sender.clone(),
+                    },
+                )?
+                .serve_at(
+                    OBJECT_PATH,
+                    PlayerInterface {
+                        event_sender: event_sender.clone(),
+                        state: PlaybackState::default(),
+                    },
+                )?
+                .build()
+        };
+
+        // Another instance might already own the name
+        let connection = connect(BUS_NAME.to_string())
+            .or_else(|_| connect(format!("{}.instance{}", BUS_NAME, std::process::id())))
+            .map_err(|e| format!("Unable to connect to the session bus: {}", e))?;
+
+        Ok(Mpris { connection })
+    }
+
+    /// Publishes the current track and the state of the player
+    pub fn update(&self, track: Option<&Track>, player: &Player) {
+        let iface_ref = match self
+            .connection
+            .object_server()
+            .interface::<_, PlayerInterface>(OBJECT_PATH)
+        {
+            Ok(iface_ref) => iface_ref,
+            Err(_) => return,
+        };
+
+        let mut iface = iface_ref.get_mut();
+        iface.state = PlaybackState {
+            status: match track {
+                Some(_) if player.is_paused() => "Paused",
+                Some(_) => "Playing",
+                None => "Stopped",
+            },
+            metadata: track.map(metadata).unwrap_or_default(),
+            position: track.map_or(Duration::ZERO, |_| player.position()),
+            updated: Instant::now(),
+            volume: player.volume() as f64,
+        };
+
+        // Clients are notified about the changes (the position is expected to be polled)
+        let emitter = iface_ref.signal_emitter();
+        let _ = zbus::block_on(iface.playback_status_changed(emitter));
+        let _ = zbus::block_on(iface.metadata_changed(emitter));
+        let _ = zbus::block_on(iface.volume_changed(emitter));
+    }
+}
+
+struct PlaybackState {
+    status: &'static str,
+    metadata: HashMap<String, OwnedValue>,
+    /// Position within the track when the state was updated
+    position: Duration,
+    updated: Instant,
+    volume: f64,
+}
+
+impl Default for PlaybackState {
+    fn default() -> Self {
+        PlaybackState {
+            status: "Stopped",
+            metadata: HashMap::new(),
+            position: Duration::ZERO,
+            updated: Instant::now(),
+            volume: 1.0,
+        }
+    }
+}
+
+fn metadata(track: &Track) -> HashMap<String, OwnedValue> {
+    // Object paths may only contain ASCII letters, digits and underscores
+    let id: String = track
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let track_id = format!("/org/halkara/track/{}", id);
+
+    let mut values = vec![
+        (
+            "mpris:trackid",
+            ObjectPath::try_from(track_id.as_str())
+                .map(Value::from)
+                .ok(),
+        ),
+        ("xesam:title", Some(Value::from(track.title.as_str()))),
+        (
+            "xesam:artist",
+            Some(Value::from(vec![track.user.name.as_str()])),
+        ),
+        (
+            "mpris:length",
+            Some(Value::from(track.duration as i64 * 1_000_000)),
+        ),
+    ];
+    if let Some(artwork) = track.artwork.as_ref().and_then(|a| a.large.as_deref()) {
+        values.push(("mpris:artUrl", Some(Value::from(artwork))));
+    }
+    if let Some(url) = track.get_url() {
+        values.push(("xesam:url", Some(Value::from(url))));
+    }
+
+    values
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value?.try_into().ok()?)))
+        .collect()
+}
+
+/// The org.mpris.MediaPlayer2 interface
+struct Root {
+    event_sender: Sender<Event>,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn quit(&self) {
+        let _ = self.event_sender.send(Event::Quit);
+    }
+
+    fn raise(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> &str {
+        "Halkara"
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<&str> {
+        vec!["https"]
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+/// The org.mpris.MediaPlayer2.Player interface
+struct PlayerInterface {
+    event_sender: Sender<Event>,
+    state: PlaybackState,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl PlayerInterface {
+    fn next(&self) {
+        let _ = self.event_sender.send(Event::Next);
+    }
+
+    fn previous(&self) {
+        let _ = self.event_sender.send(Event::Previous);
+    }
+
+    fn pause(&self) {
+        if self.state.status == "Playing" {
+            let _ = self.event_sender.send(Event::Pause);
+        }
+    }
+
+    fn play_pause(&self) {
+        let _ = self.event_sender.send(Event::Pause);
+    }
+
+    /// Stopping isn't supported, the track is paused instead
+    fn stop(&self) {
+        self.pause();
+    }
+
+    fn play(&self) {
+        if self.state.status == "Paused" {
+            let _ = self.event_sender.send(Event::Pause);
+        }
+    }
+
+    fn seek(&self, _offset: i64) {}
+
+    fn set_position(&self, _track_id: ObjectPath<'_>, _position: i64) {}
+
+    fn open_uri(&self, uri: &str) -> zbus::fdo::Result<()> {
+        let playable = Playable::parse(uri).map_err(zbus::fdo::Error::InvalidArgs)?;
+        let _ = self
+            .event_sender
+            .send(Event::Queue(QueueCommand::Add(playable, Placement::Now)));
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        self.state.status
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn set_rate(&mut self, _rate: f64) {}
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        self.state
+            .metadata
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), value.try_clone().ok()?)))
+            .collect()
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.state.volume
+    }
+
+    #[zbus(property)]
+    fn set_volume(&mut self, volume: f64) {
+        let volume = volume.clamp(0.0, 1.0);
+        self.state.volume = volume;
+        let _ = self.event_sender.send(Event::SetVolume(volume as f32));
+    }
+
+    /// Position within the track in microseconds
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        let mut position = self.state.position;
+        if self.state.status == "Playing" {
+            position += self.state.updated.elapsed();
+        }
+        position.as_micros() as i64
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        false
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,197 @@
+use crate::audius::feed::Feed;
+use crate::audius::genre::Genre;
+use crate::audius::trending::TimeRange;
+
+/// First path segments of Audius URLs that are pages of the app instead of user profiles
+const RESERVED_ROUTES: [&str; 30] = [
+    "app",
+    "audio",
+    "chat",
+    "check",
+    "dashboard",
+    "deactivated",
+    "download",
+    "error",
+    "explore",
+    "favorites",
+    "feed",
+    "history",
+    "legal",
+    "library",
+    "login",
+    "messages",
+    "notifications",
+    "oauth",
+    "payouts",
+    "premium-tracks",
+    "privacy",
+    "purchases",
+    "rewards",
+    "search",
+    "settings",
+    "signin",
+    "signup",
+    "terms",
+    "trending",
+    "upload",
+];
+
+/// Kind of content an Audius URL points to
+pub enum UrlKind {
+    Track,
+    Playlist,
+    User,
+}
+
+/// Something that can be played
+///
+/// Playables are validated when parsing, so malformed input is rejected before any request is
+/// sent to Audius.
+pub enum Playable {
+    /// The local favorites
+    Favorites,
+    Feed(Feed),
+    /// Playlist by id
+    Playlist(String),
+    /// Radio seeded from the track behind the URL
+    Radio(String),
+    /// Track by id
+    Track(String),
+    Trending {
+        genre: Option<Genre>,
+        time: Option<TimeRange>,
+    },
+    TrendingPlaylists {
+        time: Option<TimeRange>,
+    },
+    /// Link to a track, playlist or user on Audius
+    Url(UrlKind, String),
+    /// All tracks of a user by handle
+    User(String),
+    UserFavorites(String),
+    UserPlaylists(String),
+    UserReposts(String),
+}
+
+impl Playable {
+    pub fn parse(str: &str) -> Result<Playable, String> {
+        if str.starts_with("https://") || str.starts_with("http://") {
+            return parse_url(str);
+        }
+
+        let (kind, arg) = match str.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg)),
+            None => (str, None),
+        };
+        match (kind, arg) {
+            ("favorites", None) => Ok(Playable::Favorites),
+            ("best-new-releases", None) => Ok(Playable::Feed(Feed::BestNewReleases)),
+            ("feeling-lucky", None) => Ok(Playable::Feed(Feed::FeelingLucky)),
+            ("remixables", None) => Ok(Playable::Feed(Feed::Remixables)),
+            ("underground", None) => Ok(Playable::Feed(Feed::Underground)),
+            ("playlist", arg) => parse_id(arg, "Usage: playlist:<id>").map(Playable::Playlist),
+            ("radio", arg) => match arg.map(|url| (parse_url(url), url)) {
+                Some((Ok(Playable::Url(UrlKind::Track, _)), url)) => {
+                    Ok(Playable::Radio(url.to_string()))
+                }
+                Some((Ok(_), url)) => Err(url.to_owned() + " is not a link to a track"),
+                Some((Err(err), _)) => Err(err),
+                _ => Err(String::from("Usage: radio:<track-url>")),
+            },
+            ("track", arg) => parse_id(arg, "Usage: track:<id>").map(Playable::Track),
+            ("trending", arg) => {
+                let mut parts = arg.unwrap_or_default().split(':');
+                let genre = non_empty(parts.next()).map(Genre::parse).transpose()?;
+                let time = non_empty(parts.next()).map(TimeRange::parse).transpose()?;
+                if parts.next().is_some() {
+                    return Err(String::from("Usage: trending[:<genre>[:<time>]]"));
+                }
+                Ok(Playable::Trending { genre, time })
+            }
+            ("trending-playlists", arg) => match arg {
+                Some(time) if time.contains(':') => {
+                    Err(String::from("Usage: trending-playlists[:<time>]"))
+                }
+                _ => Ok(Playable::TrendingPlaylists {
+                    time: non_empty(arg).map(TimeRange::parse).transpose()?,
+                }),
+            },
+            ("user", arg) => parse_handle(arg, "Usage: user:<handle>").map(Playable::User),
+            ("user-favorites", arg) => {
+                parse_handle(arg, "Usage: user-favorites:<handle>").map(Playable::UserFavorites)
+            }
+            ("user-playlists", arg) => {
+                parse_handle(arg, "Usage: user-playlists:<handle>").map(Playable::UserPlaylists)
+            }
+            ("user-reposts", arg) => {
+                parse_handle(arg, "Usage: user-reposts:<handle>").map(Playable::UserReposts)
+            }
+            _ => Err(str.to_owned() + " is not a valid playable"),
+        }
+    }
+}
+
+/// Determines what an Audius URL points to (without contacting Audius)
+///
+/// The tabs of a user profile are mapped to the matching playables.
+fn parse_url(url: &str) -> Result<Playable, String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let mut segments = rest.split('/').filter(|segment| !segment.is_empty());
+
+    if !matches!(segments.next(), Some("audius.co" | "www.audius.co")) {
+        return Err(url.to_owned() + " is not an Audius URL");
+    }
+    let error = || url.to_owned() + " is not a link to a track, playlist or user";
+    let segments: Vec<&str> = segments.collect();
+    let handle = match segments.first() {
+        Some(handle) if !RESERVED_ROUTES.contains(handle) => {
+            parse_handle(Some(handle), "").map_err(|_| error())?
+        }
+        _ => return Err(error()),
+    };
+    let url_playable = |kind| Ok(Playable::Url(kind, url.to_string()));
+    match segments[1..] {
+        [] => url_playable(UrlKind::User),
+        ["tracks"] => Ok(Playable::User(handle)),
+        ["reposts"] => Ok(Playable::UserReposts(handle)),
+        ["playlists" | "albums"] => Ok(Playable::UserPlaylists(handle)),
+        ["collectibles"] => Err(error()),
+        ["playlist" | "album", _] => url_playable(UrlKind::Playlist),
+        [_] => url_playable(UrlKind::Track),
+        _ => Err(error()),
+    }
+}
+
+fn parse_id(id: Option<&str>, usage: &str) -> Result<String, String> {
+    match id {
+        Some(id) if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Ok(id.to_string())
+        }
+        Some(id) if !id.is_empty() => Err(id.to_owned() + " is not a valid id"),
+        _ => Err(usage.to_string()),
+    }
+}
+
+fn parse_handle(handle: Option<&str>, usage: &str) -> Result<String, String> {
+    let handle = handle.map(|handle| handle.trim_start_matches('@'));
+    match handle {
+        Some(handle)
+            if !handle.is_empty()
+                && handle
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') =>
+        {
+            Ok(handle.to_string())
+        }
+        Some(handle) if !handle.is_empty() => Err(handle.to_owned() + " is not a valid handle"),
+        _ => Err(usage.to_string()),
+    }
+}
+
+fn non_empty(str: Option<&str>) -> Option<&str> {
+    str.filter(|str| !str.is_empty())
+}
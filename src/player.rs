@@ -1,18 +1,46 @@
+use std::cell::Cell;
 use std::io::Cursor;
 use std::str;
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
 
 use crate::audius::track;
 use crate::Event;
 
 pub struct Player {
     _stream: OutputStream,
-    _stream_handle: OutputStreamHandle,
+    stream_handle: OutputStreamHandle,
     event_sender: Sender<Event>,
     sink: Arc<RwLock<Sink>>,
+    clock: Cell<PlaybackClock>,
+}
+
+/// Keeps track of the position within the current track
+#[derive(Clone, Copy)]
+struct PlaybackClock {
+    start_position: Duration,
+    started: Instant,
+    paused_since: Option<Instant>,
+    paused: Duration,
+}
+
+impl PlaybackClock {
+    fn new(start_position: Duration) -> PlaybackClock {
+        PlaybackClock {
+            start_position,
+            started: Instant::now(),
+            paused_since: None,
+            paused: Duration::ZERO,
+        }
+    }
+
+    fn position(&self) -> Duration {
+        let now = self.paused_since.unwrap_or_else(Instant::now);
+        self.start_position + now.duration_since(self.started) - self.paused
+    }
 }
 
 impl Player {
@@ -26,17 +54,16 @@ impl Player {
 
         Player {
             _stream,
-            _stream_handle: stream_handle,
+            stream_handle,
             event_sender,
             sink: Arc::new(RwLock::new(sink)),
+            clock: Cell::new(PlaybackClock::new(Duration::ZERO)),
         }
     }
 
-    pub fn play(&self, track: &track::Track) -> Result<(), String> {
-        let stream = match track.get_stream() {
-            Ok(stream) => stream,
-            Err(e) => return Err(e.to_string()),
-        };
+    /// Plays the track starting at the given position
+    pub fn play(&self, track: &track::Track, position: Duration) -> Result<(), String> {
+        let stream = track.get_stream()?;
         let error_msg = if stream.len() < 16384 {
             str::from_utf8(&stream).unwrap_or("Error: invalid stream format")
         } else {
@@ -49,7 +76,8 @@ impl Player {
         match decoder {
             Ok(decoder) => match self.sink.read() {
                 Ok(sink) => {
-                    sink.append(decoder);
+                    sink.append(decoder.skip_duration(position));
+                    self.clock.set(PlaybackClock::new(position));
                     self.sleep_until_end();
                     Ok(())
                 }
@@ -61,16 +89,57 @@ impl Player {
 
     pub fn pause(&self) {
         if let Ok(sink) = self.sink.read() {
+            let mut clock = self.clock.get();
             if sink.is_paused() {
                 sink.play();
+                if let Some(paused_since) = clock.paused_since.take() {
+                    clock.paused += paused_since.elapsed();
+                }
             } else {
                 sink.pause();
+                clock.paused_since = Some(Instant::now());
+            }
+            self.clock.set(clock);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sink.read().is_ok_and(|sink| sink.is_paused())
+    }
+
+    /// Position within the current track
+    pub fn position(&self) -> Duration {
+        self.clock.get().position()
+    }
+
+    /// Stops the current track, which ends it immediately
+    pub fn skip(&self) {
+        if let Ok(sink) = self.sink.read() {
+            sink.stop();
+        }
+
+        // A stopped sink can't be used anymore
+        if let Ok(mut sink) = self.sink.write() {
+            if let Ok(new_sink) = Sink::try_new(&self.stream_handle) {
+                new_sink.set_volume(sink.volume());
+                *sink = new_sink;
             }
         }
     }
 
     const VOLUME_ADJUST: f32 = 0.69;
 
+    /// Linear volume (1.0 is the original volume)
+    pub fn volume(&self) -> f32 {
+        self.sink.read().map_or(1.0, |sink| sink.volume())
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        if let Ok(sink) = self.sink.read() {
+            sink.set_volume(volume.clamp(0.0, 1.0));
+        }
+    }
+
     pub fn volume_up(&self) {
         if let Ok(sink) = self.sink.read() {
             let vol = sink.volume() / Player::VOLUME_ADJUST;
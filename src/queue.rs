@@ -0,0 +1,208 @@
+use crate::audius::{OrderedTrack, TrackGroup};
+use crate::playable::Playable;
+use serde::{Deserialize, Serialize};
+
+/// Where to put newly added tracks
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// Right after the current track, which will be skipped
+    Now,
+    /// Right after the current track
+    Next,
+    /// At the end of the queue
+    End,
+}
+
+/// Commands for editing the queue at runtime
+///
+/// Positions refer to the upcoming tracks, starting with 1 for the next track.
+pub enum QueueCommand {
+    Add(Playable, Placement),
+    Clear,
+    Move(usize, usize),
+    Remove(usize),
+}
+
+impl QueueCommand {
+    pub fn parse(str: &str) -> Result<QueueCommand, String> {
+        let mut parts = str.split_whitespace();
+        match parts.next().unwrap_or_default() {
+            "add" => parse_add(parts.next(), Placement::End, "Usage: :add <playable>"),
+            "next" => parse_add(parts.next(), Placement::Next, "Usage: :next <playable>"),
+            "now" => parse_add(parts.next(), Placement::Now, "Usage: :now <playable>"),
+            "clear" => Ok(QueueCommand::Clear),
+            "move" => match (parts.next(), parts.next()) {
+                (Some(from), Some(to)) => Ok(QueueCommand::Move(
+                    parse_position(from)?,
+                    parse_position(to)?,
+                )),
+                _ => Err(String::from("Usage: :move <from> <to>")),
+            },
+            "remove" => Ok(QueueCommand::Remove(parse_position(
+                parts.next().unwrap_or("1"),
+            )?)),
+            command => Err(command.to_owned() + " is not a valid command"),
+        }
+    }
+}
+
+fn parse_add(
+    playable: Option<&str>,
+    placement: Placement,
+    usage: &str,
+) -> Result<QueueCommand, String> {
+    let playable = playable.ok_or_else(|| usage.to_string())?;
+    Ok(QueueCommand::Add(Playable::parse(playable)?, placement))
+}
+
+fn parse_position(str: &str) -> Result<usize, String> {
+    match str.parse::<usize>() {
+        Ok(position) if position > 0 => Ok(position),
+        _ => Err(str.to_owned() + " is not a valid position"),
+    }
+}
+
+/// Track groups to be played with the position of the current track
+pub struct Queue {
+    pub(crate) track_groups: Vec<TrackGroup>,
+    /// Index of the current track across all groups
+    current: Option<usize>,
+}
+
+impl Queue {
+    pub fn new(track_groups: Vec<TrackGroup>) -> Queue {
+        Queue {
+            track_groups,
+            current: None,
+        }
+    }
+
+    /// Index of the current track across all groups
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Group and track index of the current track
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.current.and_then(|current| self.locate(current))
+    }
+
+    /// Moves on to the next track and returns its group and track index
+    pub fn advance(&mut self) -> Option<(usize, usize)> {
+        let next = self.first_upcoming();
+        let position = self.locate(next)?;
+        self.current = Some(next);
+        Some(position)
+    }
+
+    /// Moves back, so advancing plays the previous track (or the first track again)
+    pub fn rewind(&mut self) {
+        self.current = self.current.and_then(|current| current.checked_sub(2));
+    }
+
+    pub fn append(&mut self, mut track_groups: Vec<TrackGroup>) {
+        self.track_groups.append(&mut track_groups);
+    }
+
+    /// Inserts tracks that have been played already in front of all other tracks
+    pub fn insert_played(&mut self, track_groups: Vec<TrackGroup>) {
+        let len: usize = track_groups.iter().map(|g| g.tracks.len()).sum();
+        self.current = (self.first_upcoming() + len).checked_sub(1);
+        self.track_groups.splice(0..0, track_groups);
+    }
+
+    /// Inserts the tracks right after the current track
+    pub fn insert_next(&mut self, track_groups: Vec<TrackGroup>) {
+        match self.current.and_then(|current| self.locate(current)) {
+            Some((group, track_index)) => {
+                let tracks = track_groups
+                    .into_iter()
+                    .flat_map(TrackGroup::into_tracks_with_origin);
+                self.track_groups[group]
+                    .tracks
+                    .splice(track_index + 1..track_index + 1, tracks);
+            }
+            None => {
+                self.track_groups.splice(0..0, track_groups);
+            }
+        }
+    }
+
+    /// Removes all upcoming tracks
+    pub fn clear(&mut self) {
+        match self.current.and_then(|current| self.locate(current)) {
+            Some((group, track_index)) => {
+                self.track_groups.truncate(group + 1);
+                self.track_groups[group].tracks.truncate(track_index + 1);
+            }
+            None => self.track_groups.clear(),
+        }
+    }
+
+    pub fn remove(&mut self, position: usize) -> Result<OrderedTrack, String> {
+        self.check_position(position)?;
+        let (group, track_index) = self
+            .locate(self.first_upcoming() + position - 1)
+            .ok_or_else(|| format!("There is no track at position {}", position))?;
+        Ok(self.track_groups[group].tracks.remove(track_index))
+    }
+
+    pub fn move_track(&mut self, from: usize, to: usize) -> Result<(), String> {
+        self.check_position(from)?;
+        self.check_position(to)?;
+        let source_group = self
+            .locate(self.first_upcoming() + from - 1)
+            .map(|(group, _)| self.track_groups[group].name.clone());
+        let mut track = self.remove(from)?;
+
+        // Insert in front of the track currently at the target position (or at the end)
+        let target = self.first_upcoming() + to - 1;
+        let (group, track_index) = self.locate(target).unwrap_or_else(|| {
+            let last_group = self.track_groups.len() - 1;
+            (last_group, self.track_groups[last_group].tracks.len())
+        });
+        if let Some(source_group) = source_group {
+            if source_group != self.track_groups[group].name {
+                track.origin.get_or_insert(source_group);
+            }
+        }
+        self.track_groups[group].tracks.insert(track_index, track);
+
+        Ok(())
+    }
+
+    /// Number of tracks after the current one
+    pub fn upcoming(&self) -> usize {
+        self.len() - self.first_upcoming()
+    }
+
+    fn len(&self) -> usize {
+        self.track_groups.iter().map(|g| g.tracks.len()).sum()
+    }
+
+    /// Makes sure that the position refers to an upcoming track
+    fn check_position(&self, position: usize) -> Result<(), String> {
+        if position == 0 || position > self.upcoming() {
+            return Err(format!("There is no track at position {}", position));
+        }
+
+        Ok(())
+    }
+
+    fn first_upcoming(&self) -> usize {
+        self.current.map_or(0, |current| current + 1)
+    }
+
+    /// Converts an index across all groups into a group and track index
+    fn locate(&self, mut index: usize) -> Option<(usize, usize)> {
+        for (group_index, group) in self.track_groups.iter().enumerate() {
+            if index < group.tracks.len() {
+                return Some((group_index, index));
+            }
+            index -= group.tracks.len();
+        }
+
+        None
+    }
+}
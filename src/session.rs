@@ -0,0 +1,166 @@
+use crate::audius::track::{get_track, Track};
+use crate::audius::{OrderedTrack, TrackGroup, WORKERS};
+use crate::queue::Queue;
+use crate::utils::{data_dir, load_data, parallel_map, save_data};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::Duration;
+
+const SESSION_FILE: &str = "session.json";
+
+/// Queue and playback position saved when quitting
+#[derive(Deserialize, Serialize)]
+pub struct Session {
+    track_groups: Vec<SessionGroup>,
+    /// Index of the current track across all groups
+    current: usize,
+    /// Position within the current track in milliseconds
+    position: u64,
+}
+
+/// Part of a session that has been resolved in the background
+pub enum Restored {
+    /// The track that was playing when quitting with the position within it
+    Current(TrackGroup, Duration),
+    /// All other tracks, split into the ones played before and after the current track
+    Remaining {
+        played: Vec<TrackGroup>,
+        upcoming: Vec<TrackGroup>,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+struct SessionGroup {
+    name: String,
+    tracks: Vec<SessionTrack>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SessionTrack {
+    id: String,
+    index: usize,
+    origin: Option<String>,
+}
+
+impl Session {
+    pub fn new(queue: &Queue, position: Duration) -> Session {
+        Session {
+            track_groups: queue
+                .track_groups
+                .iter()
+                .map(|group| SessionGroup {
+                    name: group.name.clone(),
+                    tracks: group
+                        .tracks
+                        .iter()
+                        .map(|t| SessionTrack {
+                            id: t.track.id.clone(),
+                            index: t.index,
+                            origin: t.origin.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            current: queue.current().unwrap_or_default(),
+            position: position.as_millis() as u64,
+        }
+    }
+
+    pub fn load() -> Result<Option<Session>, String> {
+        load_data(SESSION_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_data(SESSION_FILE, self)
+    }
+
+    pub fn remove() {
+        if let Some(dir) = data_dir() {
+            let _ = fs::remove_file(dir.join(SESSION_FILE));
+        }
+    }
+
+    /// Resolves the track that was playing when quitting
+    ///
+    /// It's returned in a group of its own together with the position within it.
+    pub fn restore_current(&self) -> Option<Restored> {
+        let mut index = self.current;
+        for group in &self.track_groups {
+            if let Some(session_track) = group.tracks.get(index) {
+                let track = get_track(&session_track.id).ok()?;
+                let track_group = TrackGroup {
+                    tracks: vec![session_track.to_ordered_track(track)],
+                    name: group.name.clone(),
+                };
+                return Some(Restored::Current(
+                    track_group,
+                    Duration::from_millis(self.position),
+                ));
+            }
+            index -= group.tracks.len();
+        }
+
+        None
+    }
+
+    /// Resolves all tracks except the current one
+    ///
+    /// Tracks that can't be resolved anymore are skipped.
+    pub fn restore_remaining(self) -> Restored {
+        let current = self.current;
+        let mut played = Vec::with_capacity(self.track_groups.len());
+        let mut upcoming = Vec::with_capacity(self.track_groups.len());
+        let mut session_tracks = Vec::new();
+        for (group_index, group) in self.track_groups.into_iter().enumerate() {
+            for groups in [&mut played, &mut upcoming] {
+                groups.push(TrackGroup {
+                    tracks: Vec::new(),
+                    name: group.name.clone(),
+                });
+            }
+            session_tracks.extend(group.tracks.into_iter().map(|t| (group_index, t)));
+        }
+
+        let session_tracks = session_tracks
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| *index != current)
+            .collect();
+        let results = parallel_map(
+            session_tracks,
+            WORKERS,
+            |(index, (group_index, session_track))| {
+                let track = get_track(&session_track.id).ok();
+                (
+                    index,
+                    group_index,
+                    track.map(|t| session_track.to_ordered_track(t)),
+                )
+            },
+        );
+        for (index, group_index, track) in results {
+            if let Some(track) = track {
+                let groups = if index < current {
+                    &mut played
+                } else {
+                    &mut upcoming
+                };
+                groups[group_index].tracks.push(track);
+            }
+        }
+
+        played.retain(|group| !group.tracks.is_empty());
+        upcoming.retain(|group| !group.tracks.is_empty());
+        Restored::Remaining { played, upcoming }
+    }
+}
+
+impl SessionTrack {
+    fn to_ordered_track(&self, track: Track) -> OrderedTrack {
+        OrderedTrack {
+            track,
+            index: self.index,
+            origin: self.origin.clone(),
+        }
+    }
+}
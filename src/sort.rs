@@ -0,0 +1,58 @@
+use crate::audius::track::Track;
+use crate::audius::OrderedTrack;
+use crate::date::Date;
+use std::cmp::Ordering;
+
+pub enum SortKey {
+    Artist,
+    Duration,
+    Favorites,
+    Plays,
+    Released,
+    Title,
+}
+
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Sort keys by priority (the first key is the most significant one)
+pub struct SortOrder {
+    pub(crate) keys: Vec<(SortKey, SortDirection)>,
+}
+
+impl SortOrder {
+    /// Sorts the tracks (stable, so tracks with equal keys keep their order)
+    pub fn apply(&self, tracks: &mut [OrderedTrack]) {
+        tracks.sort_by(|a, b| {
+            self.keys
+                .iter()
+                .map(|(key, direction)| {
+                    let ordering = compare(key, &a.track, &b.track);
+                    match direction {
+                        SortDirection::Ascending => ordering,
+                        SortDirection::Descending => ordering.reverse(),
+                    }
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+}
+
+fn compare(key: &SortKey, a: &Track, b: &Track) -> Ordering {
+    match key {
+        SortKey::Artist => a.user.name.to_lowercase().cmp(&b.user.name.to_lowercase()),
+        SortKey::Duration => a.duration.cmp(&b.duration),
+        SortKey::Favorites => a.favorite_count.cmp(&b.favorite_count),
+        SortKey::Plays => a.play_count.cmp(&b.play_count),
+        SortKey::Released => {
+            // Tracks without a release date are sorted as if they were released first
+            let release_date =
+                |t: &Track| t.release_date.as_deref().and_then(Date::parse_release_date);
+            release_date(a).cmp(&release_date(b))
+        }
+        SortKey::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+    }
+}
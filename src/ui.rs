@@ -1,4 +1,7 @@
 use crate::audius::TrackGroup;
+use crate::control::Status;
+use crate::queue::{Placement, QueueCommand};
+use crate::session::Restored;
 use std::sync::mpsc::Sender;
 
 pub mod compact;
@@ -12,6 +15,9 @@ pub trait HalkaraUi {
     fn setup(&mut self);
     fn start_reader(&self, sender: Sender<Event>);
     fn display(&self, track_groups: &[TrackGroup], group: usize, track_index: usize);
+    /// Called after the queue has been changed while the track is playing
+    fn refresh(&self, track_groups: &[TrackGroup], group: usize, track_index: usize);
+    fn info(&self, msg: &str);
     fn error(&self, err: &str);
     fn cleanup(&self);
 }
@@ -22,10 +28,20 @@ pub enum UiVariant {
     Ncurses,
 }
 
-#[derive(Debug)]
 pub enum Event {
+    Block,
+    Enqueue(Result<Vec<TrackGroup>, String>, Placement),
+    Favorite,
+    Next,
     Pause,
+    Previous,
+    Queue(QueueCommand),
     Quit,
+    RadioBatch(Result<TrackGroup, String>),
+    Resolved(Result<Vec<TrackGroup>, String>),
+    Restored(Restored),
+    SetVolume(f32),
+    Status(Sender<Status>),
     TrackEnd,
     VolumeUp,
     VolumeDown,
@@ -21,16 +21,27 @@ impl HalkaraUi for Compact {
     fn display(&self, track_groups: &[TrackGroup], group: usize, track_index: usize) {
         let mut duration = track_groups[group].tracks[track_index].track.get_duration();
         duration.truncate(6);
-        let line = format!(
+        let mut line = format!(
             "{: >3} [{: >6}] {} - {}",
             track_groups[group].tracks[track_index].index,
             duration,
             track_groups[group].tracks[track_index].track.user.name,
             track_groups[group].tracks[track_index].track.title
         );
+        if let Some(origin) = &track_groups[group].tracks[track_index].origin {
+            line = format!("{} ({})", line, origin);
+        }
         println!("{}", line);
     }
 
+    fn refresh(&self, track_groups: &[TrackGroup], group: usize, track_index: usize) {
+        println!("{}", super::log::up_next(track_groups, group, track_index));
+    }
+
+    fn info(&self, msg: &str) {
+        println!("{}", msg);
+    }
+
     fn error(&self, msg: &str) {
         eprintln!("{}", msg);
     }
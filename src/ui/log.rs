@@ -1,9 +1,15 @@
 use super::{utils::term_width, Event, HalkaraUi};
 use crate::audius::TrackGroup;
+use crate::date::Date;
+use crate::playable::Playable;
+use crate::queue::{Placement, QueueCommand};
 use std::borrow::BorrowMut;
-use std::io::{stdin, BufRead};
+use std::io::{stdin, stdout, BufRead, Write};
 use std::sync::mpsc::Sender;
 
+/// Maximum number of characters of a track description
+const DESCRIPTION_LENGTH: usize = 100;
+
 pub struct Log;
 
 impl Log {
@@ -22,18 +28,50 @@ impl HalkaraUi for Log {
     fn display(&self, track_groups: &[TrackGroup], group: usize, track_index: usize) {
         println!();
         print_rank(track_groups[group].tracks[track_index].index);
+        let track = &track_groups[group].tracks[track_index].track;
+        println!("Title: {}", track.title);
+        println!("User: {}", track.user.name);
+        println!("Duration: {}", track.get_duration());
+        if let Some(origin) = &track_groups[group].tracks[track_index].origin {
+            println!("From: {}", origin);
+        }
+        if let Some(genre) = track.genre.as_deref().filter(|g| !g.is_empty()) {
+            println!("Genre: {}", genre);
+        }
+        if let Some(mood) = track.mood.as_deref().filter(|m| !m.is_empty()) {
+            println!("Mood: {}", mood);
+        }
+        let tags = track.get_tags();
+        if !tags.is_empty() {
+            println!("Tags: {}", tags.join(", "));
+        }
+        if let Some(release_date) = track.release_date.as_deref() {
+            match Date::parse_release_date(release_date) {
+                Some(date) => println!("Released: {}", date),
+                None => println!("Released: {}", release_date),
+            }
+        }
         println!(
-            "Title: {}",
-            track_groups[group].tracks[track_index].track.title
-        );
-        println!(
-            "User: {}",
-            track_groups[group].tracks[track_index].track.user.name
-        );
-        println!(
-            "Duration: {}",
-            track_groups[group].tracks[track_index].track.get_duration()
+            "Plays: {} | Reposts: {} | Favorites: {}",
+            track.play_count, track.repost_count, track.favorite_count
         );
+        if let Some(description) = track.get_short_description(DESCRIPTION_LENGTH) {
+            println!("Description: {}", description);
+        }
+        if let Some(url) = track.get_url() {
+            println!("URL: {}", url);
+        }
+        if let Some(artwork) = track.get_artwork_url() {
+            println!("Artwork: {}", artwork);
+        }
+    }
+
+    fn refresh(&self, track_groups: &[TrackGroup], group: usize, track_index: usize) {
+        println!("{}", up_next(track_groups, group, track_index));
+    }
+
+    fn info(&self, msg: &str) {
+        println!("{}", msg);
     }
 
     fn error(&self, msg: &str) {
@@ -59,6 +97,21 @@ fn print_rank(rank: usize) {
     println!("{} #{:0>3} {}{}", half_line, rank, half_line, filler);
 }
 
+pub(crate) fn up_next(track_groups: &[TrackGroup], group: usize, track_index: usize) -> String {
+    let mut upcoming = track_groups[group].tracks[track_index + 1..]
+        .iter()
+        .chain(track_groups[group + 1..].iter().flat_map(|g| &g.tracks));
+    match upcoming.next() {
+        Some(next) => format!(
+            "Up next: {} - {} ({} tracks in queue)",
+            next.track.user.name,
+            next.track.title,
+            upcoming.count() + 1
+        ),
+        None => String::from("Queue is empty"),
+    }
+}
+
 pub(crate) fn event_reader(sender: Sender<Event>) {
     let mut pressed_keys = Vec::new();
     loop {
@@ -68,6 +121,50 @@ pub(crate) fn event_reader(sender: Sender<Event>) {
                 .lock()
                 .read_line(&mut line)
                 .expect("Reading line from stdin");
+
+            // Lines starting with a colon contain a command instead of keys
+            if let Some(command) = line.trim().strip_prefix(':') {
+                match QueueCommand::parse(command) {
+                    Ok(command) => sender
+                        .send(Event::Queue(command))
+                        .expect("Sending queue event"),
+                    Err(err) => eprintln!("{}", err),
+                }
+                continue;
+            }
+
+            // Pasted URLs are added to the queue
+            let trimmed = line.trim();
+            if trimmed.starts_with("https://") || trimmed.starts_with("http://") {
+                let playable = match Playable::parse(trimmed) {
+                    Ok(playable) => playable,
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        continue;
+                    }
+                };
+                print!("Play [n]ow, ne[x]t or [a]ppend (default)? ");
+                stdout().flush().expect("Flushing stdout");
+                let mut answer = String::new();
+                stdin()
+                    .lock()
+                    .read_line(&mut answer)
+                    .expect("Reading line from stdin");
+                let placement = match answer.trim() {
+                    "n" => Placement::Now,
+                    "x" => Placement::Next,
+                    "a" | "" => Placement::End,
+                    _ => {
+                        eprintln!("{} is not a valid choice", answer.trim());
+                        continue;
+                    }
+                };
+                sender
+                    .send(Event::Queue(QueueCommand::Add(playable, placement)))
+                    .expect("Sending queue event");
+                continue;
+            }
+
             pressed_keys.append(line.chars().collect::<Vec<char>>().borrow_mut());
         }
 
@@ -79,6 +176,14 @@ pub(crate) fn event_reader(sender: Sender<Event>) {
             ' ' => {
                 sender.send(Event::Pause).expect("Sending pause event");
             }
+            'b' => {
+                sender.send(Event::Block).expect("Sending block event");
+            }
+            'f' => {
+                sender
+                    .send(Event::Favorite)
+                    .expect("Sending favorite event");
+            }
             '+' => {
                 sender
                     .send(Event::VolumeUp)
@@ -36,7 +36,8 @@ impl HalkaraUi for Ncurses {
     }
 
     fn start_reader(&self, _sender: Sender<Event>) {
-        // Unsupported due to multithreading
+        // Unsupported due to multithreading (so there's no prompt for pasted URLs and queue
+        // commands either)
         /*std::thread::spawn(move || {
             let mut key;
             loop {
@@ -55,7 +56,12 @@ impl HalkaraUi for Ncurses {
     }
 
     fn display(&self, track_groups: &[TrackGroup], group: usize, track_index: usize) {
-        self.update_header(&track_groups[group].name);
+        self.update_header(
+            track_groups[group].tracks[track_index]
+                .origin
+                .as_ref()
+                .unwrap_or(&track_groups[group].name),
+        );
 
         let (mut maxy, mut maxx) = (0, 0);
         getmaxyx(stdscr(), &mut maxy, &mut maxx);
@@ -110,6 +116,14 @@ impl HalkaraUi for Ncurses {
         refresh();
     }
 
+    fn refresh(&self, track_groups: &[TrackGroup], group: usize, track_index: usize) {
+        self.display(track_groups, group, track_index);
+    }
+
+    fn info(&self, msg: &str) {
+        self.update_footer(msg);
+    }
+
     fn error(&self, msg: &str) {
         self.update_footer(msg);
     }
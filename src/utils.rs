@@ -1,3 +1,15 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::hash::Hash;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+use std::sync::Mutex;
+use std::thread;
+
 #[macro_export]
 macro_rules! unwrap_or_return {
     ( $e:expr ) => {
@@ -8,46 +20,229 @@ macro_rules! unwrap_or_return {
     };
 }
 
-/// Linear congruential generator
-struct Lcg {
-    m: u32,
-    state: u32,
+/// Pseudorandom number generator (xoshiro256**)
+pub struct Rng {
+    state: [u64; 4],
 }
 
-impl Lcg {
-    fn new() -> Lcg {
-        let m = 2147483647;
-        Lcg {
-            m,
-            state: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_or_else(|_| 69696969, |duration| duration.as_secs() as u32)
-                % m,
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // Expand the seed with SplitMix64 (an all-zero state must be avoided)
+        let mut splitmix_state = seed;
+        let mut splitmix = || {
+            splitmix_state = splitmix_state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = splitmix_state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        Rng {
+            state: [splitmix(), splitmix(), splitmix(), splitmix()],
         }
     }
 
+    fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
     /// Int in range (max_val is exclusive)
-    fn rand_int_range(&mut self, max_val: u32) -> u32 {
-        let a = 48271;
-        let c = 0;
+    fn rand_int_range(&mut self, max_val: usize) -> usize {
+        ((self.next_u64() as u128 * max_val as u128) >> 64) as usize
+    }
 
-        self.state = (a * self.state + c) % self.m;
-        ((self.state as f64 / self.m as f64) * max_val as f64) as u32
+    /// Float in range [0, 1)
+    fn rand_float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
     }
 }
 
+/// Seed based on the current time and the process id
+pub fn random_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(69696969, |duration| duration.as_nanos() as u64);
+    nanos ^ ((std::process::id() as u64) << 32)
+}
+
 /// Knuth shuffle
-pub fn shuffle<T>(vec: &mut Vec<T>) {
-    let n = vec.len();
-    let mut lcg = Lcg::new();
-    for i in 0..n - 1 {
-        let j = i + lcg.rand_int_range(n as u32) as usize * (n - i) / n;
+pub fn shuffle<T>(vec: &mut [T], rng: &mut Rng) {
+    for i in (1..vec.len()).rev() {
+        let j = rng.rand_int_range(i + 1);
         vec.swap(i, j);
     }
 }
 
-pub fn shuffle_n<T>(vec: &mut Vec<T>, n: usize) {
-    for _ in 0..n {
-        shuffle(vec);
+/// Shuffles the items while spreading items with the same key as evenly as possible
+///
+/// Items with a higher weight tend to come before other items with the same key.
+pub fn spread_shuffle<T, K, F, W>(vec: &mut Vec<T>, rng: &mut Rng, key: F, weight: W)
+where
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+    W: Fn(&T) -> f64,
+{
+    // Group by key, each item gets a weighted random sampling key (Efraimidis-Spirakis)
+    let mut group_indices = HashMap::new();
+    let mut groups: Vec<Vec<(f64, T)>> = Vec::new();
+    for item in vec.drain(..) {
+        let i = *group_indices.entry(key(&item)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        let sampling_key = rng
+            .rand_float()
+            .powf(1.0 / weight(&item).max(f64::MIN_POSITIVE));
+        groups[i].push((sampling_key, item));
+    }
+
+    // Distribute the items of each group evenly with a random offset
+    let mut positioned = Vec::new();
+    for mut group in groups {
+        group.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let n = group.len() as f64;
+        let offset = rng.rand_float() / n;
+        for (i, (_, item)) in group.into_iter().enumerate() {
+            positioned.push((offset + i as f64 / n, item));
+        }
+    }
+
+    positioned.sort_by(|a, b| a.0.total_cmp(&b.0));
+    vec.extend(positioned.into_iter().map(|(_, item)| item));
+}
+
+/// Applies a function to all items using a bounded number of worker threads
+///
+/// The results are handed to `on_result` in the order of the items, each one as soon as all
+/// results before it are available.
+pub fn parallel_for_each<T, R, F, C>(items: Vec<T>, workers: usize, f: F, mut on_result: C)
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    C: FnMut(R),
+{
+    let len = items.len();
+    let items = Mutex::new(items.into_iter().enumerate());
+    let (sender, receiver) = channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, std::cmp::max(len, 1)) {
+            let sender = sender.clone();
+            let items = &items;
+            let f = &f;
+            scope.spawn(move || loop {
+                // Release the lock before working on the item
+                let next = items.lock().expect("Locking items").next();
+                let (index, item) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                sender.send((index, f(item))).expect("Sending result");
+            });
+        }
+        drop(sender);
+
+        // Results finishing early have to wait for the ones before them
+        let mut results: Vec<Option<R>> = (0..len).map(|_| None).collect();
+        let mut next = 0;
+        for (index, result) in receiver {
+            results[index] = Some(result);
+            while let Some(result) = results.get_mut(next).and_then(Option::take) {
+                on_result(result);
+                next += 1;
+            }
+        }
+    });
+}
+
+/// Applies a function to all items using a bounded number of worker threads
+///
+/// The results are returned in the order of the items.
+pub fn parallel_map<T, R, F>(items: Vec<T>, workers: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let mut results = Vec::with_capacity(items.len());
+    parallel_for_each(items, workers, f, |result| results.push(result));
+    results
+}
+
+/// Levenshtein distance between two strings (case insensitive)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    // Only the previous row of the distance matrix is needed
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
     }
+
+    previous[b.len()]
+}
+
+/// Directory for persistent application data (e.g. the play history)
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// Reads a JSON file from the data directory (a missing file results in `None`)
+pub fn load_data<T: DeserializeOwned>(file: &str) -> Result<Option<T>, String> {
+    let path = data_dir()
+        .ok_or("Unable to determine the data directory")?
+        .join(file);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Unable to read {}: {}", path.display(), err)),
+    };
+    ureq::serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|err| format!("Unable to parse {}: {}", path.display(), err))
+}
+
+/// Writes a JSON file to the data directory
+///
+/// The content is written to a temporary file first and then renamed, so a crash while writing
+/// can't leave a truncated file behind.
+pub fn save_data<T: Serialize>(file: &str, value: &T) -> Result<(), String> {
+    let dir = data_dir().ok_or("Unable to determine the data directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let content = ureq::serde_json::to_string(value).map_err(|e| e.to_string())?;
+    let tmp_path = dir.join(format!("{}.{}.tmp", file, std::process::id()));
+    fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+    fs::rename(&tmp_path, dir.join(file)).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        e.to_string()
+    })
+}
+
+/// Directory for user editable configuration files (e.g. the blocklist)
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(fallback),
+    };
+    Some(base.join("halkara"))
 }